@@ -32,6 +32,61 @@
 //!     pub b: u64,
 //! }
 //! ```
+//!
+//! Tuple structs are supported, where fields are constructed in order:
+//!
+//! ```ignore
+//! #[derive(Construct)]
+//! pub struct Wrap<T>(pub T, pub u64);
+//! ```
+//!
+//! Arrays with a length literal are supported,
+//! where the element type is `u64` or implements `Construct`:
+//!
+//! ```ignore
+//! #[derive(Construct)]
+//! pub struct Sys<T> {
+//!     pub inners: [T; 2],
+//!     pub a: u64,
+//! }
+//! ```
+//!
+//! `FieldNames` names the arguments in order,
+//! where the fields of inner types are prefixed with the field name, e.g. `foo.a`:
+//!
+//! ```ignore
+//! #[derive(Construct, FieldNames)]
+//! pub struct Bar<T = ()> {
+//!     pub foo: T,
+//!     pub a: u64,
+//!     pub b: u64,
+//! }
+//! ```
+//!
+//! `CoreRules` is derived from `#[rule(...)]` attributes on the struct or its fields,
+//! where named fields are in scope and the rules are combined using `and!`:
+//!
+//! ```ignore
+//! #[derive(Construct, CoreRules)]
+//! #[rule(imply(a, b))]
+//! pub struct Foo {
+//!     pub a: u64,
+//!     pub b: u64,
+//! }
+//! ```
+//!
+//! Since attributes are parsed as meta items, `#[rule(...)]` only accepts
+//! function calls with names or nested calls as arguments, e.g. `imply(a, and(b, c))`.
+//! Other expressions are written as a string, e.g. using fields of inner systems:
+//!
+//! ```ignore
+//! #[derive(Construct, CoreRules)]
+//! #[rule = "imply(foo.a, b) & b"]
+//! pub struct Bar {
+//!     pub foo: Foo,
+//!     pub b: u64,
+//! }
+//! ```
 
 extern crate proc_macro;
 extern crate syn;
@@ -40,8 +95,8 @@ extern crate quote;
 
 use proc_macro::{TokenStream};
 use syn::{
-    Body, Ident, VariantData, PolyTraitRef, Ty, TyParamBound,
-    TraitBoundModifier, WherePredicate, WhereBoundPredicate
+    Attribute, Body, ConstExpr, Ident, Lit, MetaItem, VariantData, PolyTraitRef, Ty, TyParamBound,
+    TraitBoundModifier, WhereClause, WherePredicate, WhereBoundPredicate
 };
 use quote::Tokens;
 
@@ -51,7 +106,7 @@ pub fn construct(input: TokenStream) -> TokenStream {
     let s = input.to_string();
 
     // Parse the string representation
-    let ast = syn::parse_derive_input(&s).unwrap();
+    let ast = parse_input(&s);
 
     // Build the impl
     let gen = impl_construct(&ast);
@@ -66,80 +121,251 @@ fn impl_construct(ast: &syn::DeriveInput) -> quote::Tokens {
         let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
         // Get field identifier and type for all struct fields.
-        let fields: Vec<(_, Ty)> = if let &VariantData::Struct(ref fields) = body {
-            fields.iter()
-                .map(|field| (
-                    field.ident.as_ref().unwrap(),
-                    field.ty.clone(),
-                )).collect()
-        } else {
-            panic!("Expected struct fields");
+        // Tuple struct fields have no identifier.
+        let fields: Vec<(Option<&Ident>, Ty)> = match *body {
+            VariantData::Struct(ref fields) | VariantData::Tuple(ref fields) => {
+                fields.iter()
+                    .map(|field| (
+                        field.ident.as_ref(),
+                        field.ty.clone(),
+                    )).collect()
+            }
+            VariantData::Unit => panic!("Expected struct fields"),
         };
 
-        // Add constraints and compute offsets.
+        // Map arguments to struct fields, keeping track of the offset.
         let mut where_clause = where_clause.clone();
-        let mut offsets = Tokens::new();
-        let mut i = 0;
-        let mut ns = vec![];
-        for &(_, ref ty) in &fields {
-            let ty_ident = if let &Ty::Path(_, ref parameters) = ty {
-                parameters.segments[0].ident.clone()
-            } else {
-                panic!("Could not find type identifier.")
-            };
-            if ty_ident != &Ident::new("u64") {
-                // Add `T: Construct` constraint.
-                where_clause.predicates.push(WherePredicate::BoundPredicate(WhereBoundPredicate {
-                    bound_lifetimes: vec![],
-                    bounded_ty: ty.clone(),
-                    bounds: vec![TyParamBound::Trait(PolyTraitRef {
-                        bound_lifetimes: vec![],
-                        trait_ref: Ident::new("Construct").into()
-                    }, TraitBoundModifier::None)]
-                }));
-                offsets.append(
-                    format!("let n{} = <{} as Construct>::n();", i, ty_ident)
-                );
-                i += 1;
-            } else {
-                ns.push(i);
-            }
-        }
-
-        // Map arguments to struct fields.
         let mut field_tokens = Tokens::new();
-        let mut i = 0;
-        for &(ref field, ref ty) in &fields {
-            field_tokens.append(field);
-            field_tokens.append(":");
-            let ty_ident = if let &Ty::Path(_, ref parameters) = ty {
-                parameters.segments[0].ident.clone()
-            } else {
-                panic!("Could not find type identifier.")
-            };
-            if ty_ident == &Ident::new("u64") {
-                field_tokens.append("vs[");
-                if ns[i] != 0 {
-                    field_tokens.append(format!("n{}+", ns[i]-1));
+        let mut offset = Offset::new();
+        for &(field, ref ty) in &fields {
+            if let Some(field) = field {
+                field_tokens.append(field);
+                field_tokens.append(":");
+            }
+            match *ty {
+                Ty::Array(ref elem, ConstExpr::Lit(Lit::Int(len, _))) => {
+                    add_constraint(&mut where_clause, elem);
+                    field_tokens.append("[");
+                    for _ in 0..len {
+                        field_tokens.append(construct_expr(elem, &offset));
+                        field_tokens.append(",");
+                        offset.add(elem);
+                    }
+                    field_tokens.append("]");
+                }
+                Ty::Array(..) => panic!("Expected array length to be an integer literal."),
+                Ty::Path(..) => {
+                    add_constraint(&mut where_clause, ty);
+                    field_tokens.append(construct_expr(ty, &offset));
+                    offset.add(ty);
                 }
-                field_tokens.append(&format!("{}", i));
-                field_tokens.append("]");
-                i += 1;
-            } else {
-                field_tokens.append("Construct::construct(vs)");
+                _ => panic!("Could not find type identifier."),
             }
             field_tokens.append(",");
         }
 
+        let body = if let VariantData::Tuple(_) = *body {
+            quote! {#name(#field_tokens)}
+        } else {
+            quote! {#name {#field_tokens}}
+        };
+
         quote! {
             impl #impl_generics Construct for #name #ty_generics #where_clause {
                 fn construct(vs: &[u64]) -> Self {
-                    #offsets
-                    #name {
-                        #field_tokens
+                    #body
+                }
+            }
+        }
+    } else {panic!("Must be a struct.")}
+}
+
+#[proc_macro_derive(CoreRules, attributes(rule))]
+pub fn core_rules(input: TokenStream) -> TokenStream {
+    let s = input.to_string();
+    let ast = parse_input(&s);
+    let gen = impl_core_rules(&ast);
+    gen.parse().unwrap()
+}
+
+fn impl_core_rules(ast: &syn::DeriveInput) -> quote::Tokens {
+    if let Body::Struct(ref body) = ast.body {
+        let name = &ast.ident;
+        let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+        // Bind named fields, such that rules refer to fields by name.
+        // Inner `Construct` types are bound by reference.
+        let mut bindings = Tokens::new();
+        let mut rules = vec![];
+        push_rules(&mut rules, &ast.attrs);
+        for field in body.fields() {
+            push_rules(&mut rules, &field.attrs);
+            if let Some(ref ident) = field.ident {
+                if is_u64(&field.ty) {
+                    bindings.append(quote! {#[allow(unused_variables)] let #ident = self.#ident;});
+                } else {
+                    bindings.append(quote! {#[allow(unused_variables)] let #ident = &self.#ident;});
+                }
+            }
+        }
+
+        let rules = if rules.is_empty() {quote! {T}} else {quote! {and!(#(#rules),*)}};
+        quote! {
+            impl #impl_generics CoreRules for #name #ty_generics #where_clause {
+                fn core_rules(&self) -> u64 {
+                    #bindings
+                    #rules
+                }
+            }
+        }
+    } else {panic!("Must be a struct.")}
+}
+
+#[proc_macro_derive(FieldNames)]
+pub fn field_names(input: TokenStream) -> TokenStream {
+    let s = input.to_string();
+    let ast = parse_input(&s);
+    let gen = impl_field_names(&ast);
+    gen.parse().unwrap()
+}
+
+fn impl_field_names(ast: &syn::DeriveInput) -> quote::Tokens {
+    if let Body::Struct(ref body) = ast.body {
+        let name = &ast.ident;
+        let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+        // Push names in argument order, using the position for tuple struct fields.
+        let mut where_clause = where_clause.clone();
+        let mut pushes = Tokens::new();
+        for (i, field) in body.fields().iter().enumerate() {
+            let field_name = match field.ident {
+                Some(ref ident) => ident.to_string(),
+                None => i.to_string(),
+            };
+            match field.ty {
+                Ty::Array(ref elem, ConstExpr::Lit(Lit::Int(len, _))) => {
+                    add_bound(&mut where_clause, elem, "FieldNames");
+                    for j in 0..len {
+                        pushes.append(push_names(&format!("{}[{}]", field_name, j), elem));
                     }
                 }
+                Ty::Array(..) => panic!("Expected array length to be an integer literal."),
+                Ty::Path(..) => {
+                    add_bound(&mut where_clause, &field.ty, "FieldNames");
+                    pushes.append(push_names(&field_name, &field.ty));
+                }
+                _ => panic!("Could not find type identifier."),
+            }
+        }
+
+        quote! {
+            impl #impl_generics FieldNames for #name #ty_generics #where_clause {
+                fn field_names() -> Vec<String> {
+                    let mut names = vec![];
+                    #pushes
+                    names
+                }
             }
         }
     } else {panic!("Must be a struct.")}
 }
+
+/// Generates the statement pushing the names of a field,
+/// prefixing the names of inner types with the field name.
+fn push_names(field_name: &str, ty: &Ty) -> Tokens {
+    if is_u64(ty) {
+        quote! {names.push(#field_name.to_string());}
+    } else {
+        quote! {
+            for name in <#ty as FieldNames>::field_names() {
+                names.push(format!("{}.{}", #field_name, name));
+            }
+        }
+    }
+}
+
+/// Parses the input of a derive.
+///
+/// Attributes are parsed as meta items, so other expressions in `#[rule(...)]` fail here.
+fn parse_input(s: &str) -> syn::DeriveInput {
+    syn::parse_derive_input(s).unwrap_or_else(|err| panic!(
+        "{}. Rules in `#[rule(...)]` must be function calls on names, e.g. `imply(a, b)`, \
+         use `#[rule = \"<expr>\"]` for other expressions", err))
+}
+
+/// Collects the expressions of `#[rule(...)]` and `#[rule = "..."]` attributes.
+fn push_rules(rules: &mut Vec<Tokens>, attrs: &[Attribute]) {
+    for attr in attrs {
+        match attr.value {
+            MetaItem::List(ref ident, ref items) if ident == "rule" => {
+                for item in items {rules.push(quote! {#item})}
+            }
+            MetaItem::NameValue(ref ident, Lit::Str(ref src, _)) if ident == "rule" => {
+                let expr = syn::parse_expr(src)
+                    .unwrap_or_else(|err| panic!("Could not parse rule `{}`: {}", src, err));
+                rules.push(quote! {#expr});
+            }
+            MetaItem::Word(ref ident) | MetaItem::NameValue(ref ident, _) if ident == "rule" => {
+                panic!("Expected `#[rule(<expr>, ...)]` or `#[rule = \"<expr>\"]`")
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns `true` if the type is `u64`.
+fn is_u64(ty: &Ty) -> bool {
+    if let &Ty::Path(None, ref path) = ty {
+        path.segments.len() == 1 && path.segments[0].ident == Ident::new("u64")
+    } else {false}
+}
+
+/// Adds `T: Construct` constraint, unless the type is `u64`.
+fn add_constraint(where_clause: &mut WhereClause, ty: &Ty) {
+    add_bound(where_clause, ty, "Construct")
+}
+
+/// Adds a trait constraint, unless the type is `u64`.
+fn add_bound(where_clause: &mut WhereClause, ty: &Ty, trait_name: &str) {
+    if is_u64(ty) {return}
+    where_clause.predicates.push(WherePredicate::BoundPredicate(WhereBoundPredicate {
+        bound_lifetimes: vec![],
+        bounded_ty: ty.clone(),
+        bounds: vec![TyParamBound::Trait(PolyTraitRef {
+            bound_lifetimes: vec![],
+            trait_ref: Ident::new(trait_name).into()
+        }, TraitBoundModifier::None)]
+    }));
+}
+
+/// Generates the expression constructing a type from the arguments at an offset.
+fn construct_expr(ty: &Ty, offset: &Offset) -> String {
+    if is_u64(ty) {format!("vs[{}]", offset.expr())}
+    else if offset.is_zero() {"Construct::construct(vs)".into()}
+    else {format!("Construct::construct(&vs[{}..])", offset.expr())}
+}
+
+/// Offset into the arguments, as a constant plus the sizes of `Construct` types.
+struct Offset {
+    constant: usize,
+    sizes: Vec<String>,
+}
+
+impl Offset {
+    fn new() -> Offset {Offset {constant: 0, sizes: vec![]}}
+
+    fn is_zero(&self) -> bool {self.constant == 0 && self.sizes.is_empty()}
+
+    /// Moves the offset past a type.
+    fn add(&mut self, ty: &Ty) {
+        if is_u64(ty) {self.constant += 1}
+        else {self.sizes.push(format!("<{} as Construct>::n()", quote!{#ty}))}
+    }
+
+    /// Generates the offset expression.
+    fn expr(&self) -> String {
+        let mut terms = self.sizes.clone();
+        if self.constant != 0 || terms.is_empty() {terms.push(format!("{}", self.constant))}
+        terms.join("+")
+    }
+}
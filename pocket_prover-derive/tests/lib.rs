@@ -2,21 +2,60 @@
 extern crate pocket_prover_derive;
 extern crate pocket_prover;
 
-use pocket_prover::Construct;
+use pocket_prover::{and, imply, BaseSystem, Construct, CoreRules, FieldNames, Prove};
 
-#[derive(Construct)]
+#[derive(Construct, FieldNames)]
 pub struct Foo {
     pub a: u64,
     pub b: u64,
 }
 
-#[derive(Construct)]
+#[derive(Construct, FieldNames)]
 pub struct Bar<T = ()> {
     pub foo: T,
     pub a: u64,
     pub b: u64,
 }
 
+#[derive(Construct, FieldNames)]
+pub struct Sys<T> {
+    pub inners: [T; 2],
+    pub a: u64,
+}
+
+#[derive(Construct)]
+pub struct Mixed<T> {
+    pub a: u64,
+    pub foo: T,
+    pub bits: [u64; 2],
+}
+
+#[derive(Construct, FieldNames)]
+pub struct Pair(pub u64, pub u64);
+
+#[derive(Construct, FieldNames)]
+pub struct Wrap<T>(pub T, pub u64);
+
+#[derive(Copy, Clone, Construct, CoreRules)]
+#[rule(imply(a, b))]
+pub struct Chain {
+    pub a: u64,
+    pub b: u64,
+    #[rule(imply(b, c))]
+    pub c: u64,
+}
+
+impl BaseSystem for Chain {}
+
+#[derive(Copy, Clone, Construct, CoreRules)]
+#[rule = "imply(chain.c, b) & imply(b, chain.a)"]
+pub struct Loop {
+    pub chain: Chain,
+    pub b: u64,
+}
+
+impl BaseSystem for Loop {}
+
 #[test]
 fn foo_a_b() {
     let vs = &[1, 2];
@@ -54,3 +93,59 @@ fn bar_bar_foo_a_b() {
     assert_eq!(bar.a, 5);
     assert_eq!(bar.b, 6);
 }
+
+#[test]
+fn sys_foo_array() {
+    let vs = &[1, 2, 3, 4, 5];
+    assert_eq!(<Sys<Foo> as Construct>::n(), 5);
+    let sys: Sys<Foo> = Construct::construct(vs);
+    assert_eq!(sys.inners[0].a, 1);
+    assert_eq!(sys.inners[0].b, 2);
+    assert_eq!(sys.inners[1].a, 3);
+    assert_eq!(sys.inners[1].b, 4);
+    assert_eq!(sys.a, 5);
+}
+
+#[test]
+fn mixed_a_foo_bits() {
+    let vs = &[1, 2, 3, 4, 5];
+    let mixed: Mixed<Foo> = Construct::construct(vs);
+    assert_eq!(mixed.a, 1);
+    assert_eq!(mixed.foo.a, 2);
+    assert_eq!(mixed.foo.b, 3);
+    assert_eq!(mixed.bits, [4, 5]);
+}
+
+#[test]
+fn wrap_pair() {
+    let vs = &[1, 2, 3];
+    assert_eq!(<Wrap<Pair> as Construct>::n(), 3);
+    let wrap: Wrap<Pair> = Construct::construct(vs);
+    assert_eq!((wrap.0).0, 1);
+    assert_eq!((wrap.0).1, 2);
+    assert_eq!(wrap.1, 3);
+}
+
+#[test]
+fn chain_rules() {
+    assert!(Chain::prove(|x| imply(x.a, x.c)));
+    assert!(!Chain::prove(|x| imply(x.c, x.a)));
+    assert_eq!(Chain::world_count(), 4);
+}
+
+#[test]
+fn loop_rules() {
+    assert!(Loop::prove(|x| imply(x.chain.c, x.chain.a)));
+    assert!(!Loop::prove(|x| imply(x.chain.a, x.chain.c)));
+    assert_eq!(Loop::world_count(), 8);
+}
+
+#[test]
+fn field_names() {
+    assert_eq!(Foo::field_names(), vec!["a", "b"]);
+    assert_eq!(Bar::<Foo>::field_names(), vec!["foo.a", "foo.b", "a", "b"]);
+    assert_eq!(Bar::<Bar<Foo>>::field_names().len(), <Bar<Bar<Foo>> as Construct>::n());
+    assert_eq!(Bar::<()>::field_names(), vec!["a", "b"]);
+    assert_eq!(Sys::<Foo>::field_names(), vec!["inners[0].a", "inners[0].b", "inners[1].a", "inners[1].b", "a"]);
+    assert_eq!(Wrap::<Pair>::field_names(), vec!["0.0", "0.1", "1"]);
+}
@@ -0,0 +1,146 @@
+//! Benchmark workloads for the solver.
+//!
+//! The workloads are random formulas of controlled size,
+//! evaluated by the same code paths as closures passed to the solver.
+//! Each workload runs once per call, such that it can be driven by an external harness,
+//! e.g. `criterion`:
+//!
+//! ```ignore
+//! use pocket_prover::bench;
+//!
+//! let f = bench::random_formula(12, 40, 0);
+//! c.bench_function("proven 12", |b| b.iter(|| bench::proven_workload(12, &f)));
+//!
+//! let g = bench::random_formula(10, 40, 1);
+//! c.bench_function("guarded 10", |b| b.iter(|| bench::countn_workload(10, &g)));
+//! c.bench_function("classical 10", |b| b.iter(|| bench::classical_workload(10, &g)));
+//! ```
+//!
+//! Requires the `bench` feature.
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::formula::Formula;
+
+/// Generates a random formula with `n` arguments and `size` binary operators.
+///
+/// The same seed gives the same formula.
+/// Negations are inserted at random and are not counted in the size.
+pub fn random_formula(n: usize, size: usize, seed: u64) -> Formula {
+    random(n, size, false, &mut StdRng::seed_from_u64(seed))
+}
+
+/// Generates a random formula like `random_formula`, where some operands are qubits.
+pub fn random_qubit_formula(n: usize, size: usize, seed: u64) -> Formula {
+    random(n, size, true, &mut StdRng::seed_from_u64(seed))
+}
+
+fn random(n: usize, size: usize, qubits: bool, rng: &mut StdRng) -> Formula {
+    use Formula::*;
+
+    assert!(n > 0, "Expected at least one argument");
+    let f = if size == 0 {Var(rng.gen_range(0..n))} else {
+        let left = rng.gen_range(0..size);
+        let a = Box::new(random(n, left, qubits, rng));
+        let b = Box::new(random(n, size - 1 - left, qubits, rng));
+        match rng.gen_range(0..5) {
+            0 => And(a, b),
+            1 => Or(a, b),
+            2 => Xor(a, b),
+            3 => Eq(a, b),
+            _ => Imply(a, b),
+        }
+    };
+    let f = if qubits && rng.gen_bool(0.2) {Qubit(Box::new(f))} else {f};
+    if rng.gen_bool(0.25) {Not(Box::new(f))} else {f}
+}
+
+/// Proves a formula with `n` arguments using `proven`.
+pub fn proven_workload(n: usize, f: &Formula) -> bool {
+    crate::proven(n, &mut |vs| f.eval(vs))
+}
+
+/// Counts the solutions of a formula with `n` arguments using `countn`.
+pub fn countn_workload(n: usize, f: &Formula) -> u64 {
+    crate::countn(n, &mut |vs| f.eval(vs))
+}
+
+/// Counts the solutions of a formula with 7 to 10 arguments
+/// using `count7_classical..count10_classical`.
+///
+/// Compare with `countn_workload` to measure the cost of preparing a round per call.
+/// Panics if the formula uses qubits.
+pub fn classical_workload(n: usize, f: &Formula) -> u64 {
+    assert!(f.is_classical(), "Expected formula without qubits");
+    match n {
+        7 => crate::count7_classical(&mut |a, b, c, d, e, g, h| f.eval(&[a, b, c, d, e, g, h])),
+        8 => crate::count8_classical(&mut |a, b, c, d, e, g, h, i| f.eval(&[a, b, c, d, e, g, h, i])),
+        9 => crate::count9_classical(&mut |a, b, c, d, e, g, h, i, j| f.eval(&[a, b, c, d, e, g, h, i, j])),
+        10 => crate::count10_classical(&mut |a, b, c, d, e, g, h, i, j, k| f.eval(&[a, b, c, d, e, g, h, i, j, k])),
+        _ => panic!("Expected 7 to 10 arguments, got {}", n),
+    }
+}
+
+/// Proves a formula using `path1_provenm`.
+///
+/// The first `fa` arguments of the formula are the function arguments,
+/// followed by `x` arguments.
+pub fn path1_workload(fa: usize, x: usize, f: &Formula) -> bool {
+    let mut args = vec![0; fa + x];
+    crate::path1_provenm(fa, x, &mut |fs: &[u64], xs: &[u64]| {
+        args[..fa].copy_from_slice(fs);
+        args[fa..].copy_from_slice(xs);
+        f.eval(&args)
+    })
+}
+
+/// Counts the cases where two arguments are equal up to homotopy level `levels` using `hom_eq`.
+pub fn hom_eq_workload(levels: u32) -> u64 {
+    crate::count2(&mut |a, b| crate::hom_eq(levels, a, b))
+}
+
+/// Proves a formula with `n` arguments for `rounds` random rounds using `measure`.
+///
+/// This is used with formulas using qubits (see `random_qubit_formula`).
+pub fn measure_workload(rounds: u32, n: usize, f: &Formula) -> bool {
+    crate::measure(rounds, || proven_workload(n, f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formula::Formula::*;
+
+    fn tautology(f: Formula) -> Formula {Or(Box::new(f.clone()), Box::new(Not(Box::new(f))))}
+
+    #[test]
+    fn workloads() {
+        let f = random_formula(12, 40, 0);
+        assert_eq!(f, random_formula(12, 40, 0));
+        assert!(f.n() <= 12);
+        assert!(f.size() > 80);
+
+        assert_eq!(countn_workload(12, &f), f.count(12));
+        assert!(proven_workload(12, &tautology(f.clone())));
+        assert_eq!(proven_workload(12, &f), f.prove(12));
+        assert!(proven_workload(20, &tautology(random_formula(20, 10, 1))));
+
+        for n in 7..11 {
+            let f = random_formula(n, 30, n as u64);
+            assert_eq!(classical_workload(n, &f), countn_workload(n, &f));
+        }
+
+        assert!(path1_workload(3, 3, &tautology(random_formula(6, 10, 2))));
+
+        assert_eq!(crate::with_round_seed(0, || hom_eq_workload(1)), 2);
+        assert!(crate::with_round_seed(0, || hom_eq_workload(10)) <= 2);
+
+        let q = random_qubit_formula(4, 10, 3);
+        assert!(measure_workload(10, 4, &tautology(q)));
+        assert!(measure_workload(10, 2, &Imply(
+            Box::new(Qual(Box::new(Var(0)), Box::new(Var(1)))),
+            Box::new(Eq(Box::new(Var(0)), Box::new(Var(1))))
+        )));
+    }
+}
@@ -0,0 +1,101 @@
+//! Cardinality constraints over columns.
+//!
+//! A cardinality constraint restricts how many of the arguments are `true`,
+//! e.g. when encoding one-hot selectors.
+//! The constraints here count the true arguments for every case,
+//! 64 cases at a time (see `column_popcount`).
+//!
+//! The XOR relations `xor3..xor10` and `xorn` are the same as `exactly_one`,
+//! not the parity of the arguments.
+//!
+//! ```rust
+//! use pocket_prover::*;
+//! use pocket_prover::cardinality::*;
+//!
+//! // A one-hot selector of 3 arguments has exactly one argument set.
+//! assert!(prove!(&mut |a, b, c| imply(exactly_one(&[a, b, c]), at_most_k(&[a, b, c], 1))));
+//! ```
+
+use crate::{and, column_popcount, not, or, orn, F, T};
+
+/// Returns `true` if at least one argument is `true`, same as `orn`.
+pub fn at_least_one(vs: &[u64]) -> u64 {orn(vs)}
+
+/// Returns `true` if at most one argument is `true`.
+pub fn at_most_one(vs: &[u64]) -> u64 {
+    let mut seen = F;
+    let mut twice = F;
+    for &v in vs {
+        twice = or(twice, and(seen, v));
+        seen = or(seen, v);
+    }
+    not(twice)
+}
+
+/// Returns `true` if exactly one argument is `true`.
+pub fn exactly_one(vs: &[u64]) -> u64 {and(at_least_one(vs), at_most_one(vs))}
+
+/// Compares the number of true arguments with `k`,
+/// returning the cases where the count is greater and the cases where it is equal.
+fn compare(vs: &[u64], k: u32) -> (u64, u64) {
+    let planes = column_popcount(vs);
+    let bits = planes.len().max((u32::BITS - k.leading_zeros()) as usize);
+    let mut gt = F;
+    let mut eq = T;
+    for b in (0..bits).rev() {
+        let p = planes.get(b).cloned().unwrap_or(F);
+        if (k >> b) & 1 == 1 {
+            eq = and(eq, p);
+        } else {
+            gt = or(gt, and(eq, p));
+            eq = and(eq, not(p));
+        }
+    }
+    (gt, eq)
+}
+
+/// Returns `true` if exactly `k` arguments are `true`.
+pub fn exactly_k(vs: &[u64], k: u32) -> u64 {compare(vs, k).1}
+
+/// Returns `true` if at most `k` arguments are `true`.
+pub fn at_most_k(vs: &[u64], k: u32) -> u64 {not(compare(vs, k).0)}
+
+/// Returns `true` if at least `k` arguments are `true`.
+pub fn at_least_k(vs: &[u64], k: u32) -> u64 {
+    let (gt, eq) = compare(vs, k);
+    or(gt, eq)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn one_hot() {
+        assert!(prove3(&mut |a, b, c| eq(exactly_one(&[a, b, c]), or3(
+            and3(a, not(b), not(c)),
+            and3(not(a), b, not(c)),
+            and3(not(a), not(b), c)
+        ))));
+        assert!(prove3(&mut |a, b, c| eq(at_most_one(&[a, b, c]), at_most_k(&[a, b, c], 1))));
+        assert!(prove5(&mut |a, b, c, d, e| eq(exactly_one(&[a, b, c, d, e]), xor5(a, b, c, d, e))));
+        assert!(prove3(&mut |a, b, c| eq(at_least_one(&[a, b, c]), at_least_k(&[a, b, c], 1))));
+        assert_eq!(exactly_one(&[]), F);
+        assert_eq!(at_most_one(&[]), T);
+    }
+
+    #[test]
+    fn counts() {
+        let vs = [P0, P1, P2, P3, P4, P5];
+        for k in 0..8 {
+            let (exactly, at_most, at_least) = (exactly_k(&vs, k), at_most_k(&vs, k), at_least_k(&vs, k));
+            for bit in 0..64u64 {
+                let count = bit.count_ones();
+                assert_eq!((exactly >> bit) & 1 == 1, count == k);
+                assert_eq!((at_most >> bit) & 1 == 1, count <= k);
+                assert_eq!((at_least >> bit) & 1 == 1, count >= k);
+            }
+        }
+    }
+}
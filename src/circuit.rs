@@ -0,0 +1,99 @@
+//! Sequential circuits unrolled over time steps.
+//!
+//! A sequential circuit is described by a step relation
+//! between the state at time `t` and the state at time `t + 1`.
+//! Unrolling the relation over `k` time steps gives a `Formula`,
+//! which is used to prove properties that hold within `k` steps (bounded model checking).
+//!
+//! ```rust
+//! use pocket_prover::circuit::*;
+//! use pocket_prover::formula::Formula;
+//!
+//! // A single bit that toggles at every step.
+//! let f = unroll(2, 1, |s, t| Formula::Eq(Box::new(t[0].clone()), Box::new(!s[0].clone())));
+//! // After two steps, the bit is back where it started.
+//! let wraps = Formula::Imply(
+//!     Box::new(f),
+//!     Box::new(Formula::Eq(Box::new(state(0, 1)[0].clone()), Box::new(state(2, 1)[0].clone())))
+//! );
+//! assert!(wraps.prove(3));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::formula::Formula;
+
+/// Gets the state arguments at a time step, where the state has `m` variables.
+///
+/// State variable `i` at time `t` is argument `t * m + i`.
+pub fn state(t: usize, m: usize) -> Vec<Formula> {
+    (t * m..(t + 1) * m).map(Formula::Var).collect()
+}
+
+/// Returns a formula that is true when the state bits equal a value.
+///
+/// State variable `i` is bit `i` of the value.
+pub fn state_is(s: &[Formula], value: u64) -> Formula {
+    s.iter().enumerate().fold(Formula::T, |acc, (i, x)| {
+        acc & if (value >> i) & 1 == 1 {x.clone()} else {!x.clone()}
+    })
+}
+
+/// Unrolls a step relation over `k` time steps, where the state has `m` variables.
+///
+/// The step relation gets the state at time `t` and the state at time `t + 1`.
+/// Returns the relation for every step combined using AND,
+/// which uses `(k + 1) * m` arguments (see `state`).
+pub fn unroll(k: usize, m: usize, step: impl Fn(&[Formula], &[Formula]) -> Formula) -> Formula {
+    (0..k).fold(Formula::T, |acc, t| acc & step(&state(t, m), &state(t + 1, m)))
+}
+
+/// Adds two bits, returning the sum and the carry.
+pub fn half_adder(a: &Formula, b: &Formula) -> (Formula, Formula) {
+    (a.clone() ^ b.clone(), a.clone() & b.clone())
+}
+
+/// Adds three bits, returning the sum and the carry.
+pub fn full_adder(a: &Formula, b: &Formula, c: &Formula) -> (Formula, Formula) {
+    let (s1, c1) = half_adder(a, b);
+    let (s2, c2) = half_adder(&s1, c);
+    (s2, c1 | c2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eq(a: &Formula, b: Formula) -> Formula {Formula::Eq(Box::new(a.clone()), Box::new(b))}
+
+    #[test]
+    fn counter() {
+        // A 2-bit counter incremented at every step.
+        let f = unroll(4, 2, |s, t| {
+            let (s0, c0) = half_adder(&s[0], &Formula::T);
+            let (s1, _) = half_adder(&s[1], &c0);
+            eq(&t[0], s0) & eq(&t[1], s1)
+        });
+        let init = state_is(&state(0, 2), 0);
+        let after = |t: usize, value: u64| Formula::Imply(
+            Box::new(f.clone() & init.clone()),
+            Box::new(state_is(&state(t, 2), value))
+        );
+        for t in 0..5 {
+            assert!(after(t, t as u64 % 4).prove(10));
+        }
+        assert!(!after(3, 0).prove(10));
+    }
+
+    #[test]
+    fn adder() {
+        let (a, b, c) = (Formula::Var(0), Formula::Var(1), Formula::Var(2));
+        let (s, carry) = full_adder(&a, &b, &c);
+        for case in 0..8u64 {
+            let assign = state_is(&[a.clone(), b.clone(), c.clone()], case);
+            let sum = case.count_ones() as u64;
+            let expected = state_is(&[s.clone(), carry.clone()], sum);
+            assert!(Formula::Imply(Box::new(assign), Box::new(expected)).prove(3));
+        }
+    }
+}
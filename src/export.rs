@@ -0,0 +1,95 @@
+//! Export of propositions to the DIMACS CNF format.
+//!
+//! The DIMACS format is read by most SAT solvers, which is used to cross-check proofs.
+//! A proposition is built at runtime as a `Formula`,
+//! which records the logical relations instead of evaluating them,
+//! and is converted to clauses (see `Formula::to_cnf_tseitin`).
+//!
+//! ```rust
+//! use pocket_prover::export::formula_to_dimacs;
+//! use pocket_prover::formula::Formula::*;
+//!
+//! let f = And(Box::new(Var(0)), Box::new(Not(Box::new(Var(1)))));
+//! assert!(formula_to_dimacs(&f).starts_with("p cnf"));
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::formula::{Formula, Literal};
+
+/// Converts clauses with `n` variables to the DIMACS format.
+///
+/// Variables are numbered from `1`, where a negative number is a negated variable.
+/// Writes the header `p cnf <variables> <clauses>` and one clause per line ending in `0`.
+pub fn to_dimacs(n: usize, clauses: &[Vec<i32>]) -> String {
+    let mut s = format!("p cnf {} {}\n", n, clauses.len());
+    for clause in clauses {
+        for lit in clause {
+            s.push_str(&format!("{} ", lit));
+        }
+        s.push_str("0\n");
+    }
+    s
+}
+
+/// Converts a literal to a DIMACS variable, where argument `i` becomes variable `i + 1`.
+impl From<Literal> for i32 {
+    fn from(lit: Literal) -> i32 {
+        let v = lit.var as i32 + 1;
+        if lit.neg {-v} else {v}
+    }
+}
+
+/// Converts clauses of literals with `n` arguments to the DIMACS format (see `to_dimacs`).
+pub fn clauses_to_dimacs(n: usize, clauses: &[Vec<Literal>]) -> String {
+    let clauses: Vec<Vec<i32>> = clauses.iter().map(|clause| {
+        clause.iter().map(|&lit| lit.into()).collect()
+    }).collect();
+    to_dimacs(n, &clauses)
+}
+
+/// Converts a formula to the DIMACS format.
+///
+/// Argument `i` of the formula becomes variable `i + 1`,
+/// followed by the auxiliary variables of the Tseitin transformation.
+/// The clauses are satisfiable if and only if the formula is satisfiable.
+///
+/// Panics if the formula uses `Qual` or `Qubit`.
+pub fn formula_to_dimacs(f: &Formula) -> String {
+    let (clauses, n) = f.to_cnf_tseitin();
+    clauses_to_dimacs(n, &clauses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formula::Formula::*;
+
+    fn header(s: &str) -> (usize, usize) {
+        let words: Vec<&str> = s.lines().next().unwrap().split_whitespace().collect();
+        assert_eq!(&words[..2], &["p", "cnf"]);
+        (words[2].parse().unwrap(), words[3].parse().unwrap())
+    }
+
+    #[test]
+    fn dimacs() {
+        let s = to_dimacs(3, &[vec![1, -2], vec![3]]);
+        assert_eq!(s, "p cnf 3 2\n1 -2 0\n3 0\n");
+        assert_eq!(header(&s), (3, 2));
+
+        let clauses = vec![
+            vec![Literal {var: 0, neg: false}, Literal {var: 1, neg: true}],
+            vec![Literal {var: 1, neg: false}],
+        ];
+        assert_eq!(clauses_to_dimacs(2, &clauses), "p cnf 2 2\n1 -2 0\n2 0\n");
+
+        let f = Imply(Box::new(And(Box::new(Var(0)), Box::new(Var(1)))), Box::new(Var(2)));
+        let s = formula_to_dimacs(&f);
+        let (clauses, n) = f.to_cnf_tseitin();
+        assert_eq!(header(&s), (n, clauses.len()));
+        assert_eq!(s.lines().count(), clauses.len() + 1);
+        assert!(s.lines().skip(1).all(|line| line.ends_with(" 0") || line == "0"));
+    }
+}
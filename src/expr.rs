@@ -0,0 +1,64 @@
+//! Runtime expressions.
+//!
+//! An `Expr` is a proposition built at runtime, e.g. when generating formulas programmatically,
+//! which is not possible with Rust closures.
+//! It is the same type as `formula::Formula`,
+//! such that expressions are evaluated, counted and proven using the same methods.
+//!
+//! The variants `Var`, `Not`, `And`, `Or`, `Xor`, `Imply`, `Eq`, `T` and `F`
+//! work with and without the `quality` feature.
+//! `Qual` and `Qubit` require the `quality` feature,
+//! and without it, evaluating an expression that uses them panics.
+//! Use `Expr::is_classical` to check an expression built at runtime before evaluating it.
+//!
+//! ```rust
+//! use pocket_prover::expr::Expr;
+//!
+//! // `x0 => x1 => ... => x4` generated at runtime.
+//! let chain = (0..4).rev().fold(Expr::Var(4), |acc, i| Expr::Imply(Box::new(Expr::Var(i)), Box::new(acc)));
+//! assert_eq!(chain.count(5), 31);
+//! ```
+
+pub use crate::formula::Formula as Expr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::Expr::*;
+    use crate::*;
+
+    #[test]
+    fn generated() {
+        // Transitivity of implication over a chain of `n` arguments.
+        for n in 2..8 {
+            let links = (0..n - 1).fold(Expr::T, |acc, i| {
+                And(Box::new(acc), Box::new(Imply(Box::new(Var(i)), Box::new(Var(i + 1)))))
+            });
+            let f = Imply(Box::new(links), Box::new(Imply(Box::new(Var(0)), Box::new(Var(n - 1)))));
+            assert!(f.prove(n));
+        }
+
+        let f = Or(Box::new(Var(0)), Box::new(Not(Box::new(Var(1)))));
+        assert_eq!(f.eval(&[P0, P1]), or(P0, not(P1)));
+        assert_eq!(f.count(2), count2(&mut |a, b| or(a, not(b))));
+        assert!(!f.prove(2));
+    }
+
+    #[test]
+    #[cfg(not(feature = "quality"))]
+    fn classical() {
+        let f = Eq(Box::new(Xor(Box::new(Var(0)), Box::new(Var(1)))),
+            Box::new(Not(Box::new(Eq(Box::new(Var(0)), Box::new(Var(1)))))));
+        assert!(f.is_classical());
+        assert!(f.prove(2));
+        assert_eq!(Or(Box::new(Expr::T), Box::new(Expr::F)).count(3), 8);
+        assert!(!Qubit(Box::new(Var(0))).is_classical());
+    }
+
+    #[test]
+    #[cfg(not(feature = "quality"))]
+    #[should_panic(expected = "Qubits require the `quality` feature")]
+    fn qubit_without_quality() {
+        Qubit(Box::new(Var(0))).count(1);
+    }
+}
@@ -0,0 +1,127 @@
+//! Bridge to external SAT solvers using the DIMACS format.
+//!
+//! Brute force does not scale to formulas with many arguments.
+//! For such formulas, an external SAT solver can be used instead,
+//! by converting the formula to clauses (see `export::formula_to_dimacs`).
+//!
+//! The solver is run with the path of a DIMACS file as argument,
+//! and must print the result in the format of the SAT competition
+//! (`s SATISFIABLE` followed by `v` model lines, or `s UNSATISFIABLE`).
+//! This works with solvers such as `kissat` or `cadical`.
+//!
+//! Requires the `external-solver` feature.
+
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crate::export::formula_to_dimacs;
+use crate::formula::Formula;
+
+/// The result of an external solver.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveResult {
+    /// The formula is satisfiable, with an assignment of the original arguments.
+    Sat(Vec<bool>),
+    /// The formula is unsatisfiable.
+    Unsat,
+    /// The solver gave no answer.
+    Unknown,
+}
+
+/// Parses the output of a solver, reading the assignment of the first `n` arguments.
+pub fn parse_solution(output: &str, n: usize) -> SolveResult {
+    let mut res = SolveResult::Unknown;
+    let mut assignment = vec![false; n];
+    for line in output.lines() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("s") => match words.next() {
+                Some("SATISFIABLE") => res = SolveResult::Sat(vec![]),
+                Some("UNSATISFIABLE") => res = SolveResult::Unsat,
+                _ => {}
+            },
+            Some("v") => {
+                for x in words.filter_map(|w| w.parse::<i64>().ok()) {
+                    let i = x.unsigned_abs() as usize;
+                    if i >= 1 && i <= n {assignment[i - 1] = x > 0}
+                }
+            }
+            _ => {}
+        }
+    }
+    if let SolveResult::Sat(_) = res {SolveResult::Sat(assignment)} else {res}
+}
+
+/// Solves a formula using an external SAT solver.
+///
+/// Exports the formula as clauses in the DIMACS format to a temporary file,
+/// runs the solver and parses the result.
+/// The assignment covers the arguments of the formula (see `Formula::n`).
+///
+/// Returns an error of kind `InvalidInput` if the formula uses `Qual` or `Qubit`,
+/// since these can not be converted to clauses.
+pub fn solve_external<P: AsRef<Path>>(f: &Formula, solver_path: P) -> io::Result<SolveResult> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    if !f.is_classical() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+            "Qubits can not be converted to clauses"));
+    }
+    let file = std::env::temp_dir().join(format!(
+        "pocket_prover-{}-{}.cnf",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    if let Err(err) = std::fs::write(&file, formula_to_dimacs(f)) {
+        let _ = std::fs::remove_file(&file);
+        return Err(err);
+    }
+    let output = Command::new(solver_path.as_ref()).arg(&file).output();
+    // The result is already known, so failing to clean up is not an error.
+    let _ = std::fs::remove_file(&file);
+    Ok(parse_solution(&String::from_utf8_lossy(&output?.stdout), f.n()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::Formula::*;
+
+    fn var(i: usize) -> Box<Formula> {Box::new(Var(i))}
+
+    #[test]
+    fn solution() {
+        assert_eq!(parse_solution("c comment\ns SATISFIABLE\nv 1 -2\nv 3 0\n", 2),
+            SolveResult::Sat(vec![true, false]));
+        assert_eq!(parse_solution("s UNSATISFIABLE\n", 2), SolveResult::Unsat);
+        assert_eq!(parse_solution("", 2), SolveResult::Unknown);
+    }
+
+    #[test]
+    fn qubits() {
+        let f = Qubit(var(0));
+        let err = solve_external(&f, "solver-that-is-never-run").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    /// Run with `cargo test -- --ignored` and
+    /// the environment variable `POCKET_PROVER_SAT_SOLVER` set to a solver.
+    #[test]
+    #[ignore = "needs POCKET_PROVER_SAT_SOLVER"]
+    fn solver() {
+        let solver = std::env::var("POCKET_PROVER_SAT_SOLVER")
+            .expect("POCKET_PROVER_SAT_SOLVER is not set");
+        let f = And(Box::new(Imply(var(0), var(1))), Box::new(And(var(0), Box::new(Not(var(2))))));
+        match solve_external(&f, &solver).unwrap() {
+            SolveResult::Sat(xs) => {
+                assert_eq!(xs, vec![true, true, false]);
+            }
+            x => panic!("Expected satisfiable, got {:?}", x),
+        }
+        let g = And(var(0), Box::new(Not(var(0))));
+        assert_eq!(solve_external(&g, &solver).unwrap(), SolveResult::Unsat);
+    }
+}
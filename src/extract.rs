@@ -28,7 +28,11 @@
 //! }
 //! ```
 
-use crate::{id, not};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use crate::{and, eq, id, imply, not, proven, PredN};
 
 /// Converts a boolean to a bit.
 pub fn bit(b: bool) -> u64 {if b {1} else {0}}
@@ -39,6 +43,69 @@ pub fn bitv(b: u64) -> bool {b != 0}
 /// If the bit argument is `0`, returns `not`, else `id`.
 pub fn bitf(b: u64) -> fn(u64) -> u64 {if b == 0 {not} else {id}}
 
+/// Finds pairs of candidate expressions that are equal according to a theory.
+///
+/// Returns the indices `(i, j)` with `i < j` of every pair where the theory
+/// of `n` arguments proves `candidates[i] = candidates[j]`.
+///
+/// This discovers equalities that otherwise must be read from the tables of `println_extract!`.
+pub fn discover_pattern(
+    n: usize,
+    th: &PredN,
+    candidates: &[&PredN]
+) -> Vec<(usize, usize)> {
+    let mut res = vec![];
+    for i in 0..candidates.len() {
+        for j in i + 1..candidates.len() {
+            if proven(n, &mut |vs| imply(th(vs), eq(candidates[i](vs), candidates[j](vs)))) {
+                res.push((i, j));
+            }
+        }
+    }
+    res
+}
+
+/// Extracts a truth table from a theory of `n` arguments, same as `println_extract!`.
+///
+/// Returns a row for every bit configuration of the channels, with the first channel as highest bit,
+/// together with whether the configuration exists according to the theory.
+pub fn extract_table(
+    n: usize,
+    th: impl Fn(&[u64]) -> u64,
+    channels: &[&PredN]
+) -> Vec<(Vec<bool>, bool)> {
+    let k = channels.len();
+    (0_u64..1 << k).map(|x| {
+        let bits: Vec<u64> = (0..k).map(|i| (x >> (k - i - 1)) & 1).collect();
+        let exists = !proven(n, &mut |vs| imply(
+            th(vs),
+            not(channels.iter().zip(&bits).fold(crate::T, |acc, (ch, &b)| and(acc, bitf(b)(ch(vs)))))
+        ));
+        (bits.into_iter().map(bitv).collect(), exists)
+    }).collect()
+}
+
+/// Formats an extracted truth table (see `extract_table`) as a Markdown table.
+///
+/// There is one header per channel, followed by the header of the existence column.
+/// Cells are written as `0` or `1`.
+///
+/// Panics if the number of headers is not the number of columns.
+pub fn to_markdown(headers: &[&str], rows: &[(Vec<bool>, bool)]) -> String {
+    let line = |cells: &mut dyn Iterator<Item = String>| {
+        let cells: Vec<String> = cells.collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+    let mut s = line(&mut headers.iter().map(|h| h.to_string()));
+    s.push_str(&line(&mut headers.iter().map(|_| "---".to_string())));
+    for (bits, exists) in rows {
+        assert_eq!(headers.len(), bits.len() + 1,
+            "Expected {} headers, one per channel and one for existence", bits.len() + 1);
+        s.push_str(&line(&mut bits.iter().chain(Some(exists)).map(|&b| bit(b).to_string())));
+    }
+    s
+}
+
 /// Generates a "{}{}{}..." format for bits.
 #[macro_export]
 macro_rules! bits_format(
@@ -94,3 +161,52 @@ macro_rules! println_extract(
         });
     };
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::or;
+
+    #[test]
+    fn discover() {
+        let th = |vs: &[u64]| and(eq(vs[0], vs[1]), vs[2]);
+        let res = discover_pattern(3, &th, &[
+            &|vs| and(vs[0], vs[2]),
+            &|vs| and(vs[1], vs[2]),
+            &|vs| or(vs[0], vs[2]),
+        ]);
+        assert_eq!(res, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn table() {
+        let th = |vs: &[u64]| and(eq(vs[0], vs[1]), vs[2]);
+        let rows = extract_table(3, th, &[&|vs| and(vs[0], vs[2]), &|vs| and(vs[1], vs[2])]);
+        assert_eq!(rows, vec![
+            (vec![false, false], true),
+            (vec![false, true], false),
+            (vec![true, false], false),
+            (vec![true, true], true),
+        ]);
+    }
+
+    #[test]
+    fn markdown() {
+        let th = |vs: &[u64]| and(eq(vs[0], vs[1]), vs[2]);
+        let rows = extract_table(3, th, &[&|vs| and(vs[0], vs[2]), &|vs| and(vs[1], vs[2])]);
+        assert_eq!(to_markdown(&["(a ∧ c)", "(b ∧ c)", "(∃)"], &rows), "\
+| (a ∧ c) | (b ∧ c) | (∃) |
+| --- | --- | --- |
+| 0 | 0 | 1 |
+| 0 | 1 | 0 |
+| 1 | 0 | 0 |
+| 1 | 1 | 1 |
+");
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected 3 headers")]
+    fn markdown_mismatch() {
+        to_markdown(&["a", "(∃)"], &[(vec![true, false], true)]);
+    }
+}
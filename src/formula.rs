@@ -0,0 +1,1148 @@
+//! Propositions represented as data.
+//!
+//! A `Formula` can be built at runtime, stored and converted to other representations,
+//! which is not possible with propositions written as Rust closures.
+//!
+//! ```rust
+//! use pocket_prover::formula::Formula::*;
+//!
+//! // Modus ponens.
+//! let f = Imply(
+//!     Box::new(And(Box::new(Var(0)), Box::new(Imply(Box::new(Var(0)), Box::new(Var(1)))))),
+//!     Box::new(Var(1))
+//! );
+//! assert!(f.prove(2));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use crate::{and, chunks, countn, eq, eval_chunk, imply, measure, not, or, proven, xor, F, T};
+#[cfg(feature = "quality")]
+use crate::{qual, qubit};
+
+/// Qubits are only evaluated with the `quality` feature.
+#[cfg(not(feature = "quality"))]
+fn qual(_: u64, _: u64) -> u64 {panic!("Qubits require the `quality` feature")}
+
+/// Qubits are only evaluated with the `quality` feature.
+#[cfg(not(feature = "quality"))]
+fn qubit(_: u64) -> u64 {panic!("Qubits require the `quality` feature")}
+
+/// A proposition represented as data.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Formula {
+    /// The True proposition.
+    T,
+    /// The False proposition.
+    F,
+    /// An argument by index.
+    Var(usize),
+    /// Negation.
+    Not(Box<Formula>),
+    /// AND relation.
+    And(Box<Formula>, Box<Formula>),
+    /// OR relation.
+    Or(Box<Formula>, Box<Formula>),
+    /// XOR relation.
+    Xor(Box<Formula>, Box<Formula>),
+    /// Equality.
+    Eq(Box<Formula>, Box<Formula>),
+    /// First argument implies the second.
+    Imply(Box<Formula>, Box<Formula>),
+    /// Path semantical quality `a ~~ b`.
+    ///
+    /// Evaluation panics without the `quality` feature.
+    Qual(Box<Formula>, Box<Formula>),
+    /// Qubit prepared using a proposition as seed `~a`.
+    ///
+    /// Evaluation panics without the `quality` feature.
+    Qubit(Box<Formula>),
+}
+
+/// An argument or its negation, used in clauses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Literal {
+    /// The argument index.
+    pub var: usize,
+    /// Whether the argument is negated.
+    pub neg: bool,
+}
+
+impl Literal {
+    /// Evaluates the literal.
+    pub fn eval(&self, vs: &[u64]) -> u64 {
+        if self.neg {not(vs[self.var])} else {vs[self.var]}
+    }
+}
+
+impl core::ops::Not for Literal {
+    type Output = Literal;
+    fn not(self) -> Literal {Literal {var: self.var, neg: !self.neg}}
+}
+
+/// Evaluates clauses in conjunctive normal form.
+pub fn eval_cnf(clauses: &[Vec<Literal>], vs: &[u64]) -> u64 {
+    clauses.iter().fold(T, |acc, clause| {
+        and(acc, clause.iter().fold(F, |acc, lit| or(acc, lit.eval(vs))))
+    })
+}
+
+impl Formula {
+    /// Evaluates the formula.
+    pub fn eval(&self, vs: &[u64]) -> u64 {
+        use Formula::*;
+
+        match self {
+            T => crate::T,
+            F => crate::F,
+            Var(i) => vs[*i],
+            Not(a) => not(a.eval(vs)),
+            And(a, b) => and(a.eval(vs), b.eval(vs)),
+            Or(a, b) => or(a.eval(vs), b.eval(vs)),
+            Xor(a, b) => xor(a.eval(vs), b.eval(vs)),
+            Eq(a, b) => eq(a.eval(vs), b.eval(vs)),
+            Imply(a, b) => imply(a.eval(vs), b.eval(vs)),
+            Qual(a, b) => qual(a.eval(vs), b.eval(vs)),
+            Qubit(a) => qubit(a.eval(vs)),
+        }
+    }
+
+    /// Gets the number of arguments used by the formula.
+    ///
+    /// This is one more than the highest argument index.
+    pub fn n(&self) -> usize {
+        use Formula::*;
+
+        match self {
+            T | F => 0,
+            Var(i) => i + 1,
+            Not(a) | Qubit(a) => a.n(),
+            And(a, b) | Or(a, b) | Xor(a, b) | Eq(a, b) | Imply(a, b) | Qual(a, b) => a.n().max(b.n()),
+        }
+    }
+
+    /// Gets the argument indices used by the formula in increasing order.
+    pub fn support(&self) -> Vec<usize> {
+        fn visit(f: &Formula, vars: &mut Vec<usize>) {
+            use Formula::*;
+
+            match f {
+                T | F => {}
+                Var(i) => if let Err(j) = vars.binary_search(i) {vars.insert(j, *i)},
+                Not(a) | Qubit(a) => visit(a, vars),
+                And(a, b) | Or(a, b) | Xor(a, b) | Eq(a, b) | Imply(a, b) | Qual(a, b) => {
+                    visit(a, vars);
+                    visit(b, vars);
+                }
+            }
+        }
+
+        let mut vars = vec![];
+        visit(self, &mut vars);
+        vars
+    }
+
+    /// Maps every argument index to a new one.
+    pub fn map_vars(&self, map: &dyn Fn(usize) -> usize) -> Formula {
+        self.subst_vars(&|i| Formula::Var(map(i)))
+    }
+
+    /// Replaces every argument with a formula.
+    fn subst_vars(&self, subst: &dyn Fn(usize) -> Formula) -> Formula {
+        use Formula::*;
+
+        let bin = |a: &Formula, b: &Formula| (Box::new(a.subst_vars(subst)), Box::new(b.subst_vars(subst)));
+        match self {
+            T => T,
+            F => F,
+            Var(i) => subst(*i),
+            Not(a) => Not(Box::new(a.subst_vars(subst))),
+            Qubit(a) => Qubit(Box::new(a.subst_vars(subst))),
+            And(a, b) => {let (a, b) = bin(a, b); And(a, b)}
+            Or(a, b) => {let (a, b) = bin(a, b); Or(a, b)}
+            Xor(a, b) => {let (a, b) = bin(a, b); Xor(a, b)}
+            Eq(a, b) => {let (a, b) = bin(a, b); Eq(a, b)}
+            Imply(a, b) => {let (a, b) = bin(a, b); Imply(a, b)}
+            Qual(a, b) => {let (a, b) = bin(a, b); Qual(a, b)}
+        }
+    }
+
+    /// Substitutes an argument with a formula.
+    ///
+    /// The arguments of the replacement are inserted at the position of the substituted argument,
+    /// such that argument `j` of the replacement becomes argument `var + j`.
+    /// Arguments after the substituted one are shifted to make room.
+    ///
+    /// For example, substituting `c ∧ d` for `a` in `a => b` gives `c ∧ d => b`,
+    /// where `c, d, b` are arguments `0, 1, 2`.
+    pub fn substitute(&self, var: usize, replacement: &Formula) -> Formula {
+        let r = replacement.n();
+        let replacement = replacement.map_vars(&|j| var + j);
+        self.subst_vars(&|i| {
+            if i < var {Formula::Var(i)}
+            else if i == var {replacement.clone()}
+            else {Formula::Var(i + r - 1)}
+        })
+    }
+
+    /// Simplifies the formula by folding constants,
+    /// removing double negation and merging equal operands.
+    pub fn simplify(&self) -> Formula {
+        use Formula::*;
+
+        match self {
+            T => T,
+            F => F,
+            Var(i) => Var(*i),
+            Not(a) => match a.simplify() {
+                T => F,
+                F => T,
+                Not(a) => *a,
+                a => Not(Box::new(a)),
+            },
+            And(a, b) => match (a.simplify(), b.simplify()) {
+                (F, _) | (_, F) => F,
+                (T, x) | (x, T) => x,
+                (a, b) if a == b => a,
+                (a, b) => And(Box::new(a), Box::new(b)),
+            },
+            Or(a, b) => match (a.simplify(), b.simplify()) {
+                (T, _) | (_, T) => T,
+                (F, x) | (x, F) => x,
+                (a, b) if a == b => a,
+                (a, b) => Or(Box::new(a), Box::new(b)),
+            },
+            Xor(a, b) => match (a.simplify(), b.simplify()) {
+                (F, x) | (x, F) => x,
+                (T, x) | (x, T) => Not(Box::new(x)).simplify(),
+                (a, b) if a == b => F,
+                (a, b) => Xor(Box::new(a), Box::new(b)),
+            },
+            Eq(a, b) => match (a.simplify(), b.simplify()) {
+                (T, x) | (x, T) => x,
+                (F, x) | (x, F) => Not(Box::new(x)).simplify(),
+                (a, b) if a == b => T,
+                (a, b) => Eq(Box::new(a), Box::new(b)),
+            },
+            Imply(a, b) => match (a.simplify(), b.simplify()) {
+                (F, _) | (_, T) => T,
+                (T, x) => x,
+                (x, F) => Not(Box::new(x)).simplify(),
+                (a, b) if a == b => T,
+                (a, b) => Imply(Box::new(a), Box::new(b)),
+            },
+            Qual(a, b) => Qual(Box::new(a.simplify()), Box::new(b.simplify())),
+            Qubit(a) => Qubit(Box::new(a.simplify())),
+        }
+    }
+
+    /// Displays the formula in infix notation using names of arguments.
+    ///
+    /// Arguments without a name are written `x0, x1, ...`.
+    pub fn display<'a>(&'a self, names: &'a [&'a str]) -> Display<'a> {
+        Display {formula: self, names}
+    }
+
+    /// Substitutes an argument with a constant and simplifies.
+    pub fn cofactor(&self, var: usize, value: bool) -> Formula {
+        let c = if value {Formula::T} else {Formula::F};
+        self.subst_vars(&|i| if i == var {c.clone()} else {Formula::Var(i)}).simplify()
+    }
+
+    /// Gets the Boolean derivative with respect to an argument.
+    ///
+    /// This is true for the assignments where the argument affects the output.
+    pub fn boolean_derivative(&self, var: usize) -> Formula {
+        Formula::Xor(
+            Box::new(self.cofactor(var, true)),
+            Box::new(self.cofactor(var, false))
+        ).simplify()
+    }
+
+    /// Gets the number of nodes in the formula.
+    pub fn size(&self) -> usize {
+        use Formula::*;
+
+        match self {
+            T | F | Var(_) => 1,
+            Not(a) | Qubit(a) => 1 + a.size(),
+            And(a, b) | Or(a, b) | Xor(a, b) | Eq(a, b) | Imply(a, b) | Qual(a, b) => {
+                1 + a.size() + b.size()
+            }
+        }
+    }
+
+    /// Returns `true` if the formula does not use `Qual` or `Qubit`.
+    pub fn is_classical(&self) -> bool {
+        use Formula::*;
+
+        match self {
+            T | F | Var(_) => true,
+            Qual(..) | Qubit(..) => false,
+            Not(a) => a.is_classical(),
+            And(a, b) | Or(a, b) | Xor(a, b) | Eq(a, b) | Imply(a, b) => {
+                a.is_classical() && b.is_classical()
+            }
+        }
+    }
+
+    /// Splits the formula into the formulas combined by AND.
+    pub fn conjuncts(&self) -> Vec<&Formula> {
+        match self {
+            Formula::And(a, b) => {
+                let mut res = a.conjuncts();
+                res.extend(b.conjuncts());
+                res
+            }
+            _ => vec![self],
+        }
+    }
+
+    /// Gets the formula as an XOR of arguments and a constant, if it is affine.
+    ///
+    /// Arguments occurring twice cancel out.
+    fn linear(&self) -> Option<(Vec<usize>, bool)> {
+        use Formula::*;
+
+        fn xor_vars(a: Vec<usize>, b: Vec<usize>) -> Vec<usize> {
+            let mut res: Vec<usize> = a.iter().filter(|i| !b.contains(i)).cloned()
+                .chain(b.iter().filter(|i| !a.contains(i)).cloned()).collect();
+            res.sort();
+            res
+        }
+
+        match self {
+            T => Some((vec![], true)),
+            F => Some((vec![], false)),
+            Var(i) => Some((vec![*i], false)),
+            Not(a) => a.linear().map(|(vars, c)| (vars, !c)),
+            Xor(a, b) | Eq(a, b) => {
+                let ((va, ca), (vb, cb)) = (a.linear()?, b.linear()?);
+                let c = ca ^ cb ^ matches!(self, Eq(_, _));
+                Some((xor_vars(va, vb), c))
+            }
+            _ => None,
+        }
+    }
+
+    /// Extracts XOR clauses, if the formula is a conjunction of XOR and equivalence clauses.
+    ///
+    /// Each clause lists arguments whose XOR must equal the parity.
+    /// Returns `None` if some conjunct is not affine.
+    /// The clauses are counted in polynomial time using `count_affine`.
+    pub fn extract_xor_clauses(&self) -> Option<Vec<(Vec<usize>, bool)>> {
+        self.conjuncts().into_iter()
+            .map(|f| f.linear().map(|(vars, c)| (vars, !c)))
+            .collect()
+    }
+
+    /// Counts the number of solutions of the formula with `n` arguments,
+    /// using `count_affine` when the formula is a conjunction of XOR clauses.
+    ///
+    /// Falls back to brute force for other formulas.
+    pub fn count_auto(&self, n: usize) -> u128 {
+        match self.extract_xor_clauses() {
+            Some(clauses) => count_affine(n, &clauses),
+            None => self.count(n) as u128,
+        }
+    }
+
+    /// Counts the number of solutions of the formula with `n` arguments.
+    pub fn count(&self, n: usize) -> u64 {
+        countn(n, &mut |vs| self.eval(vs))
+    }
+
+    /// Returns `true` if the formula with `n` arguments is correct, `false` otherwise.
+    ///
+    /// Formulas using `Qual` or `Qubit` are only proven for a single random round,
+    /// use `measure_prove` instead.
+    pub fn prove(&self, n: usize) -> bool {
+        proven(n, &mut |vs| self.eval(vs))
+    }
+
+    /// Computes the truth table of the formula with `n` arguments.
+    ///
+    /// Returns one `u64` for every 64 cases,
+    /// where case `i` assigns argument `j` to bit `j` of `i`.
+    /// Cases outside the table are zero when `n < 6`.
+    pub fn truth_table(&self, n: usize) -> Vec<u64> {
+        let mut args = vec![0; n];
+        (0..chunks(n)).map(|chunk| eval_chunk(n, chunk, &mut args, &mut |vs| self.eval(vs))).collect()
+    }
+
+    /// Moves argument `order[k]` to argument `k`.
+    fn reorder(&self, order: &[usize]) -> Formula {
+        let mut pos = vec![None; order.len().max(self.n())];
+        for (k, &i) in order.iter().enumerate() {pos[i] = Some(k)}
+        self.map_vars(&|i| pos[i].expect("Argument is missing from the order"))
+    }
+
+    /// Gets the number of nodes in the reduced ordered binary decision diagram (BDD),
+    /// where `order[0]` is the top argument.
+    ///
+    /// Terminal nodes are not counted.
+    /// The diagram is not built, but the distinct sub-functions are found from the truth table,
+    /// so this is only used for formulas with few arguments.
+    pub fn bdd_size(&self, order: &[usize]) -> usize {
+        let n = order.len();
+        let table = self.reorder(order).truth_table(n);
+        let bit = |i: usize| (table[i >> 6] >> (i & 63)) & 1 == 1;
+        let mut nodes = 0;
+        for k in 0..n {
+            // Sub-functions after assigning the first `k` arguments in the order.
+            let mut subs = alloc::collections::BTreeSet::new();
+            for p in 0..1usize << k {
+                let sub: Vec<bool> = (0..1usize << (n - k)).map(|m| bit(p | m << k)).collect();
+                if sub.chunks(2).any(|c| c[0] != c[1]) {subs.insert(sub);}
+            }
+            nodes += subs.len();
+        }
+        nodes
+    }
+
+    /// Converts to algebraic normal form (Reed-Muller expansion).
+    ///
+    /// Returns monomials that are combined using XOR,
+    /// where each monomial lists the arguments combined using AND.
+    /// An empty monomial is the True proposition.
+    /// The degree of the formula is the length of the longest monomial.
+    ///
+    /// Computed by the Möbius transform over the truth table.
+    pub fn to_anf(&self) -> Vec<Vec<usize>> {
+        const MASKS: [u64; 6] = [
+            0x5555_5555_5555_5555, 0x3333_3333_3333_3333, 0x0f0f_0f0f_0f0f_0f0f,
+            0x00ff_00ff_00ff_00ff, 0x0000_ffff_0000_ffff, 0x0000_0000_ffff_ffff,
+        ];
+
+        let n = self.n();
+        let mut table = self.truth_table(n);
+        for x in &mut table {
+            for (i, mask) in MASKS.iter().enumerate().take(n) {
+                *x ^= (*x & mask) << (1 << i);
+            }
+        }
+        for j in 0..n.saturating_sub(6) {
+            for c in 0..table.len() {
+                if (c >> j) & 1 == 1 {table[c] ^= table[c ^ (1 << j)]}
+            }
+        }
+        let mut res = vec![];
+        for (c, &x) in table.iter().enumerate() {
+            for bit in (0..64).filter(|bit| (x >> bit) & 1 == 1) {
+                let case = (c as u64) << 6 | bit;
+                res.push((0..n).filter(|i| (case >> i) & 1 == 1).collect());
+            }
+        }
+        res
+    }
+
+    /// Proves the formula with `n` arguments repeatedly over random rounds.
+    ///
+    /// This is used for formulas using `Qual` or `Qubit` (see `measure`).
+    pub fn measure_prove(&self, n: usize, rounds: u32) -> bool {
+        measure(rounds, || self.prove(n))
+    }
+
+    /// Converts to conjunctive normal form using the Tseitin transformation.
+    ///
+    /// Introduces auxiliary arguments after the arguments of the formula,
+    /// such that the number of clauses grows linearly with the size of the formula.
+    /// Returns the clauses and the total number of arguments.
+    ///
+    /// The clauses are satisfiable if and only if the formula is satisfiable.
+    /// Since auxiliary arguments are determined by the original arguments,
+    /// the number of solutions is preserved.
+    ///
+    /// Panics if the formula uses `Qual` or `Qubit`.
+    pub fn to_cnf_tseitin(&self) -> (Vec<Vec<Literal>>, usize) {
+        let mut clauses = vec![];
+        let mut n = self.n();
+        let root = self.tseitin(&mut clauses, &mut n);
+        clauses.push(vec![root]);
+        (clauses, n)
+    }
+
+    fn tseitin(&self, clauses: &mut Vec<Vec<Literal>>, n: &mut usize) -> Literal {
+        use Formula::*;
+
+        let aux = |n: &mut usize| {
+            let x = Literal {var: *n, neg: false};
+            *n += 1;
+            x
+        };
+        match self {
+            T | F => {
+                let x = aux(n);
+                clauses.push(vec![if let T = self {x} else {!x}]);
+                x
+            }
+            Var(i) => Literal {var: *i, neg: false},
+            Not(a) => !a.tseitin(clauses, n),
+            Qual(..) | Qubit(..) => panic!("Qubits can not be converted to clauses"),
+            And(a, b) | Or(a, b) | Xor(a, b) | Eq(a, b) | Imply(a, b) => {
+                let a = a.tseitin(clauses, n);
+                let b = b.tseitin(clauses, n);
+                let x = aux(n);
+                match self {
+                    And(..) => {
+                        clauses.push(vec![!x, a]);
+                        clauses.push(vec![!x, b]);
+                        clauses.push(vec![x, !a, !b]);
+                    }
+                    Or(..) => {
+                        clauses.push(vec![x, !a]);
+                        clauses.push(vec![x, !b]);
+                        clauses.push(vec![!x, a, b]);
+                    }
+                    Imply(..) => {
+                        clauses.push(vec![x, a]);
+                        clauses.push(vec![x, !b]);
+                        clauses.push(vec![!x, !a, b]);
+                    }
+                    _ => {
+                        // XOR and equality only differ by the sign of the result.
+                        let x = if let Eq(..) = self {!x} else {x};
+                        clauses.push(vec![!x, a, b]);
+                        clauses.push(vec![!x, !a, !b]);
+                        clauses.push(vec![x, !a, b]);
+                        clauses.push(vec![x, a, !b]);
+                    }
+                }
+                x
+            }
+        }
+    }
+}
+
+/// Finds the weakest additional constraint that strengthens a theory into a new one.
+///
+/// Returns `d` such that `old ∧ d` is equivalent to `new`, when `new` implies `old`.
+///
+/// The weakest such constraint is `old => new`, but this is rarely readable.
+/// Instead, the conjuncts of `new` that are not redundant given `old` are searched for,
+/// and the smallest of the two forms is returned.
+pub fn weakest_addition(old: &Formula, new: &Formula) -> Formula {
+    let n = old.n().max(new.n());
+    let and_all = |fs: &[&Formula]| fs.iter().fold(Formula::T, |acc, &f| acc & f.clone()).simplify();
+    let adds = |d: &Formula| Formula::Eq(Box::new(old.clone() & d.clone()), Box::new(new.clone())).prove(n);
+
+    let mut parts = new.conjuncts();
+    let mut i = 0;
+    while i < parts.len() {
+        let mut rest = parts.clone();
+        rest.remove(i);
+        if adds(&and_all(&rest)) {parts = rest} else {i += 1}
+    }
+    let d = Formula::Imply(Box::new(old.clone()), Box::new(new.clone())).simplify();
+    let conj = and_all(&parts);
+    if adds(&conj) && conj.size() <= d.size() {conj} else {d}
+}
+
+/// Counts the number of solutions of XOR clauses with `n` arguments.
+///
+/// Each clause lists arguments whose XOR must equal the parity.
+/// Uses Gaussian elimination over GF(2), which takes polynomial time.
+/// The number of solutions is `2^(n - rank)`, or zero when the clauses are inconsistent.
+///
+/// Panics if a clause uses an argument outside `n`,
+/// or if the number of solutions does not fit in `u128`.
+pub fn count_affine(n: usize, clauses: &[(Vec<usize>, bool)]) -> u128 {
+    let words = n / 64 + 1;
+    // Stores the parity in the bit after the arguments.
+    let mut rows: Vec<Vec<u64>> = clauses.iter().map(|(vars, parity)| {
+        let mut row = vec![0; words];
+        for &i in vars {
+            assert!(i < n, "Argument {} is out of range for {} arguments", i, n);
+            row[i / 64] ^= 1 << (i % 64);
+        }
+        if *parity {row[n / 64] ^= 1 << (n % 64)}
+        row
+    }).collect();
+    let bit = |row: &[u64], i: usize| (row[i / 64] >> (i % 64)) & 1 == 1;
+    let mut rank = 0;
+    for col in 0..n {
+        let pivot = match (rank..rows.len()).find(|&r| bit(&rows[r], col)) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+        for r in 0..rows.len() {
+            if r != rank && bit(&rows[r], col) {
+                let pivot_row = rows[rank].clone();
+                for (x, y) in rows[r].iter_mut().zip(pivot_row) {*x ^= y}
+            }
+        }
+        rank += 1;
+    }
+    // A remaining row with only the parity set is a contradiction.
+    if rows[rank..].iter().any(|row| bit(row, n)) {return 0}
+    let free = n - rank;
+    1u128.checked_shl(free as u32).filter(|_| free < 128)
+        .expect("Number of solutions does not fit in `u128`")
+}
+
+impl core::str::FromStr for Formula {
+    type Err = String;
+
+    /// Parses a formula in infix notation, e.g. `(x0 & x1) => !x2`.
+    ///
+    /// Arguments are written `x0, x1, ...` and constants `T` and `F`.
+    /// The operators are `!`, `&`, `^`, `|`, `=>` and `==`, from highest to lowest precedence.
+    /// Implication is right associative, the other operators are left associative.
+    ///
+    /// With the `quality` feature, `~` is parsed as `Qubit` with the same precedence as `!`,
+    /// and `~~` as `Qual` with lower precedence than `==`.
+    fn from_str(s: &str) -> Result<Formula, String> {
+        let chars: Vec<char> = s.chars().collect();
+        let mut parser = Parser {chars: &chars, pos: 0};
+        let f = parser.qual()?;
+        parser.skip_whitespace();
+        if parser.pos < chars.len() {
+            return Err(format!("Unexpected `{}` at {}", chars[parser.pos], parser.pos));
+        }
+        Ok(f)
+    }
+}
+
+/// Displays a formula in infix notation, e.g. `(x0 & x1) => !x2`.
+///
+/// Uses the same operators as the parser, with `~~` for `Qual` and `~` for `Qubit`.
+/// Without the `quality` feature, the parser does not accept `~~` and `~`,
+/// so only formulas without qubits can be parsed back.
+/// Operands that are not arguments, constants or negations are put in parentheses.
+pub struct Display<'a> {
+    formula: &'a Formula,
+    names: &'a [&'a str],
+}
+
+impl<'a> core::fmt::Display for Display<'a> {
+    fn fmt(&self, w: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fn write(f: &Formula, names: &[&str], top: bool, w: &mut core::fmt::Formatter) -> core::fmt::Result {
+            use Formula::*;
+
+            let (a, op, b) = match f {
+                T => return write!(w, "T"),
+                F => return write!(w, "F"),
+                Var(i) => return match names.get(*i) {
+                    Some(name) => write!(w, "{}", name),
+                    None => write!(w, "x{}", i),
+                },
+                Not(a) => {write!(w, "!")?; return write(a, names, false, w)}
+                Qubit(a) => {write!(w, "~")?; return write(a, names, false, w)}
+                And(a, b) => (a, "&", b),
+                Or(a, b) => (a, "|", b),
+                Xor(a, b) => (a, "^", b),
+                Eq(a, b) => (a, "==", b),
+                Imply(a, b) => (a, "=>", b),
+                Qual(a, b) => (a, "~~", b),
+            };
+            if !top {write!(w, "(")?}
+            write(a, names, false, w)?;
+            write!(w, " {} ", op)?;
+            write(b, names, false, w)?;
+            if !top {write!(w, ")")?}
+            Ok(())
+        }
+
+        write(self.formula, self.names, true, w)
+    }
+}
+
+impl core::fmt::Display for Formula {
+    fn fmt(&self, w: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(w, "{}", self.display(&[]))
+    }
+}
+
+/// Parses formulas by recursive descent, one level of precedence per method.
+struct Parser<'a> {
+    chars: &'a [char],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {self.pos += 1}
+    }
+
+    /// Eats a token, skipping whitespace before it.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let end = self.pos + token.chars().count();
+        if end <= self.chars.len() && self.chars[self.pos..end].iter().cloned().eq(token.chars()) {
+            self.pos = end;
+            true
+        } else {false}
+    }
+
+    fn binary(
+        &mut self,
+        token: &str,
+        next: fn(&mut Self) -> Result<Formula, String>,
+        op: fn(Box<Formula>, Box<Formula>) -> Formula
+    ) -> Result<Formula, String> {
+        let mut a = next(self)?;
+        while self.eat(token) {a = op(Box::new(a), Box::new(next(self)?))}
+        Ok(a)
+    }
+
+    #[cfg(feature = "quality")]
+    fn qual(&mut self) -> Result<Formula, String> {self.binary("~~", Self::eq, Formula::Qual)}
+
+    #[cfg(not(feature = "quality"))]
+    fn qual(&mut self) -> Result<Formula, String> {self.eq()}
+
+    fn eq(&mut self) -> Result<Formula, String> {self.binary("==", Self::imply, Formula::Eq)}
+
+    fn imply(&mut self) -> Result<Formula, String> {
+        let a = self.or()?;
+        if self.eat("=>") {Ok(Formula::Imply(Box::new(a), Box::new(self.imply()?)))} else {Ok(a)}
+    }
+
+    fn or(&mut self) -> Result<Formula, String> {self.binary("|", Self::xor, Formula::Or)}
+
+    fn xor(&mut self) -> Result<Formula, String> {self.binary("^", Self::and, Formula::Xor)}
+
+    fn and(&mut self) -> Result<Formula, String> {self.binary("&", Self::unary, Formula::And)}
+
+    fn unary(&mut self) -> Result<Formula, String> {
+        if self.eat("!") {return Ok(Formula::Not(Box::new(self.unary()?)))}
+        #[cfg(feature = "quality")]
+        {if self.eat("~") {return Ok(Formula::Qubit(Box::new(self.unary()?)))}}
+        if self.eat("(") {
+            let f = self.qual()?;
+            if !self.eat(")") {return Err(format!("Expected `)` at {}", self.pos))}
+            return Ok(f);
+        }
+        if self.eat("T") {return Ok(Formula::T)}
+        if self.eat("F") {return Ok(Formula::F)}
+        if self.eat("x") {
+            let start = self.pos;
+            while self.pos < self.chars.len() && self.chars[self.pos].is_ascii_digit() {self.pos += 1}
+            let digits: String = self.chars[start..self.pos].iter().collect();
+            return digits.parse().map(Formula::Var).map_err(|_| format!("Expected argument index at {}", start));
+        }
+        Err(format!("Expected formula at {}", self.pos))
+    }
+}
+
+/// A logical system defined at runtime by rules over `n` arguments.
+///
+/// This mirrors the `Prove` trait for systems that are not known at compile time,
+/// e.g. rules loaded from a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DynSystem {
+    /// The number of arguments.
+    pub n: usize,
+    /// The rules of the system.
+    pub rules: Formula,
+}
+
+impl DynSystem {
+    /// Counts `imply(rules, f)`, same as `Prove::count`.
+    pub fn count(&self, f: &Formula) -> u64 {
+        countn(self.n, &mut |vs| imply(self.rules.eval(vs), f.eval(vs)))
+    }
+
+    /// Proves a statement according to the rules, same as `Prove::prove`.
+    pub fn prove(&self, f: &Formula) -> bool {
+        self.count(f) == 1 << self.n
+    }
+
+    /// Computes the logical probability `P(f | rules)`, same as `Prove::prob`.
+    pub fn prob(&self, f: &Formula) -> Option<f64> {
+        let models = self.rules.count(self.n);
+        if models == 0 {None}
+        else {Some(countn(self.n, &mut |vs| and(self.rules.eval(vs), f.eval(vs))) as f64 / models as f64)}
+    }
+}
+
+/// Implemented by lazily generated columns of a truth table.
+pub trait Column {
+    /// Gets the value at a row.
+    ///
+    /// Bit `i` of the row is the value of argument `i`.
+    /// A row holds the first 64 arguments, so the formula is projected to the arguments it uses
+    /// (see `Formula::support` and `Formula::map_vars`) when there are more.
+    ///
+    /// Panics if an argument does not fit in the row.
+    fn at(&self, row: u64) -> bool;
+}
+
+/// Converts a boolean to a constant column.
+fn const_column(x: bool) -> u64 {if x {T} else {F}}
+
+impl Column for Formula {
+    fn at(&self, row: u64) -> bool {
+        use Formula::*;
+
+        match self {
+            T => true,
+            F => false,
+            Var(i) => {
+                assert!(*i < 64, "Argument {} does not fit in a row of 64 arguments", i);
+                (row >> i) & 1 == 1
+            }
+            Not(a) => !a.at(row),
+            And(a, b) => a.at(row) && b.at(row),
+            Or(a, b) => a.at(row) || b.at(row),
+            Xor(a, b) => a.at(row) != b.at(row),
+            Eq(a, b) => a.at(row) == b.at(row),
+            Imply(a, b) => !a.at(row) || b.at(row),
+            // A qubit of a single row is sampled from a constant column.
+            Qual(a, b) => qual(const_column(a.at(row)), const_column(b.at(row))) & 1 == 1,
+            Qubit(a) => qubit(const_column(a.at(row))) & 1 == 1,
+        }
+    }
+}
+
+impl core::ops::Not for Formula {
+    type Output = Formula;
+    fn not(self) -> Formula {Formula::Not(Box::new(self))}
+}
+
+impl core::ops::BitAnd for Formula {
+    type Output = Formula;
+    fn bitand(self, other: Formula) -> Formula {Formula::And(Box::new(self), Box::new(other))}
+}
+
+impl core::ops::BitOr for Formula {
+    type Output = Formula;
+    fn bitor(self, other: Formula) -> Formula {Formula::Or(Box::new(self), Box::new(other))}
+}
+
+impl core::ops::BitXor for Formula {
+    type Output = Formula;
+    fn bitxor(self, other: Formula) -> Formula {Formula::Xor(Box::new(self), Box::new(other))}
+}
+
+/// Counts the number of solutions of an n-argument function over lazy columns.
+///
+/// The function gets the columns of the arguments and combines them lazily.
+/// Only arguments used by the result are enumerated,
+/// while every other argument doubles the number of solutions.
+/// This makes sparse formulas with many arguments cheap to count.
+///
+/// Panics if the number of solutions does not fit in `u128`.
+pub fn count_lazy(n: usize, fun: impl Fn(&[Formula]) -> Formula) -> u128 {
+    let (count, free) = count_projected(n, fun);
+    1u128.checked_shl(free as u32).filter(|_| free < 128)
+        .and_then(|m| (count as u128).checked_mul(m))
+        .expect("Number of solutions does not fit in `u128`")
+}
+
+/// Counts the solutions of a formula, where argument `order[k]` is enumerated as argument `k`.
+///
+/// The first 6 arguments in the order are packed into the columns of 64 cases,
+/// which is cheap, so arguments used in many places should go first.
+/// The order is also the argument order of the BDD (see `Formula::bdd_size`).
+/// The count is the same for every order.
+///
+/// Panics if an argument of the formula is missing from the order.
+pub fn count_formula_ordered(f: &Formula, order: &[usize]) -> u128 {
+    let g = f.reorder(order);
+    count_lazy(order.len(), |_| g.clone())
+}
+
+/// Returns `true` if an n-argument function over lazy columns is correct, `false` otherwise.
+///
+/// Only arguments used by the result are enumerated (see `count_lazy`).
+pub fn prove_lazy(n: usize, fun: impl Fn(&[Formula]) -> Formula) -> bool {
+    let (count, free) = count_projected(n, fun);
+    count == 1 << (n - free)
+}
+
+/// Counts solutions over the used arguments, returning the number of unused arguments.
+fn count_projected(n: usize, fun: impl Fn(&[Formula]) -> Formula) -> (u64, usize) {
+    let vars: Vec<Formula> = (0..n).map(Formula::Var).collect();
+    let f = fun(&vars);
+    let support = f.support();
+    let f = f.map_vars(&|i| support.binary_search(&i).unwrap());
+    (f.count(support.len()), n - support.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::Formula::*;
+
+    fn var(i: usize) -> Box<Formula> {Box::new(Var(i))}
+
+    #[test]
+    fn tseitin() {
+        let fs = vec![
+            Imply(Box::new(And(var(0), var(1))), var(2)),
+            Xor(Box::new(Eq(var(0), var(1))), Box::new(Not(Box::new(Or(var(1), var(2)))))),
+            And(var(0), Box::new(Not(var(0)))),
+            Or(Box::new(Formula::T), var(1)),
+            Imply(var(0), Box::new(Formula::F)),
+        ];
+        for f in &fs {
+            let (clauses, n) = f.to_cnf_tseitin();
+            assert_eq!(countn(n, &mut |vs| eval_cnf(&clauses, vs)), f.count(f.n()));
+            assert!(f.is_classical());
+        }
+        assert!(!And(var(0), Box::new(Not(Box::new(Qubit(var(1)))))).is_classical());
+    }
+
+    #[test]
+    fn ordered() {
+        // `(x0 ∧ x1) ∨ (x2 ∧ x3) ∨ (x4 ∧ x5)` is small when pairs are adjacent in the order.
+        let f = Or(Box::new(Or(Box::new(And(var(0), var(1))), Box::new(And(var(2), var(3))))),
+            Box::new(And(var(4), var(5))));
+        let good = [0, 1, 2, 3, 4, 5];
+        let bad = [0, 2, 4, 1, 3, 5];
+        assert_eq!(count_formula_ordered(&f, &good), f.count(6) as u128);
+        assert_eq!(count_formula_ordered(&f, &bad), f.count(6) as u128);
+        assert_eq!(f.bdd_size(&good), 6);
+        assert_eq!(f.bdd_size(&bad), 14);
+        assert_eq!(count_formula_ordered(&Var(2), &[2, 0, 1, 3]), 8);
+        assert_eq!(Formula::T.bdd_size(&[0]), 0);
+    }
+
+    #[test]
+    fn lazy() {
+        let f = |vs: &[Formula]| (vs[2].clone() & vs[9].clone()) | !vs[4].clone();
+        assert_eq!(count_lazy(12, f) as u64, countn(12, &mut |vs| or(and(vs[2], vs[9]), not(vs[4]))));
+        assert_eq!(count_lazy(100, f), 5 << 97);
+        assert!(prove_lazy(200, |vs| vs[3].clone() | !vs[190].clone() | vs[190].clone()));
+        assert!(!prove_lazy(200, f));
+        assert!(f(&[Var(0), Var(1), Var(2), Var(3), Var(4), Var(5), Var(6), Var(7), Var(8), Var(9)])
+            .at(0b10_0000_0100));
+    }
+
+    #[test]
+    #[should_panic(expected = "Argument 64 does not fit in a row of 64 arguments")]
+    fn column_out_of_range() {
+        Or(var(0), var(64)).at(0);
+    }
+
+    #[test]
+    fn derivative() {
+        assert_eq!(And(var(0), var(1)).boolean_derivative(0), Var(1));
+
+        let fs = vec![
+            Imply(Box::new(And(var(0), var(1))), var(2)),
+            Xor(Box::new(Eq(var(0), var(1))), Box::new(Not(Box::new(Or(var(1), var(2)))))),
+            Or(Box::new(Formula::T), var(1)),
+        ];
+        for f in &fs {
+            for v in 0..3 {
+                let pos = f.cofactor(v, true);
+                let neg = f.cofactor(v, false);
+                assert!(!pos.support().contains(&v));
+                assert_eq!(pos.count(3), 2 * (f.clone() & Var(v)).count(3));
+                assert_eq!(neg.count(3), 2 * (f.clone() & !Var(v)).count(3));
+                let d = f.boolean_derivative(v);
+                assert!(Eq(Box::new(d), Box::new(Xor(Box::new(pos), Box::new(neg)))).prove(3));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "quality")]
+    fn quality() {
+        let f = Qual(var(0), Box::new(Not(Box::new(Qubit(var(1))))));
+        assert_eq!(f.n(), 2);
+        assert_eq!(f.simplify(), f);
+        assert!(proven(2, &mut |vs| eq(f.eval(vs), qual(vs[0], not(qubit(vs[1]))))));
+        assert!(Imply(Box::new(Qual(var(0), var(1))), Box::new(Eq(var(0), var(1)))).measure_prove(2, 100));
+        assert!(!Qual(var(0), var(0)).measure_prove(1, 100));
+    }
+
+    #[test]
+    fn substitute() {
+        let f = Imply(var(0), var(1));
+        let g = f.substitute(0, &And(var(0), var(1)));
+        assert_eq!(g, Imply(Box::new(And(var(0), var(1))), var(2)));
+        assert_eq!(g.count(3), countn(3, &mut |vs| imply(and(vs[0], vs[1]), vs[2])));
+
+        // Substitution agrees with evaluating the replacement in place of the argument.
+        let f = Xor(Box::new(Eq(var(0), var(1))), Box::new(Not(Box::new(Or(var(1), var(2))))));
+        let r = Imply(var(0), var(1));
+        let g = f.substitute(1, &r);
+        assert_eq!(g.n(), 4);
+        assert_eq!(g.count(4), countn(4, &mut |vs| {
+            f.eval(&[vs[0], r.eval(&vs[1..3]), vs[3]])
+        }));
+        assert_eq!(f.substitute(2, &Formula::T).count(2), f.cofactor(2, true).count(2));
+    }
+
+    #[test]
+    fn weakest() {
+        let old = And(var(0), Box::new(Imply(var(0), var(1))));
+        let new = And(Box::new(old.clone()), var(2));
+        assert_eq!(weakest_addition(&old, &new), Var(2));
+
+        let new = And(Box::new(And(var(2), var(1))), var(0));
+        assert_eq!(weakest_addition(&old, &new), Var(2));
+        assert_eq!(weakest_addition(&old, &old), Formula::T);
+
+        // Not a strengthening, so the result falls back to implication.
+        let new = Or(var(0), var(2));
+        let d = weakest_addition(&old, &new);
+        assert_eq!(d, Imply(Box::new(old.clone()), Box::new(new.clone())));
+    }
+
+    #[test]
+    fn anf() {
+        assert_eq!(Xor(var(0), var(1)).to_anf(), vec![vec![0], vec![1]]);
+        assert_eq!(And(var(0), var(1)).to_anf(), vec![vec![0, 1]]);
+        assert_eq!(Or(var(0), var(1)).to_anf(), vec![vec![0], vec![1], vec![0, 1]]);
+        assert_eq!(Not(var(0)).to_anf(), vec![vec![], vec![0]]);
+        assert_eq!(Formula::F.to_anf(), Vec::<Vec<usize>>::new());
+
+        // The XOR of the monomials reconstructs the formula.
+        let f = Imply(Box::new(And(var(2), var(7))), Box::new(Xor(var(0), var(8))));
+        let anf = f.to_anf();
+        let g = anf.iter().fold(Formula::F, |acc, m| {
+            acc ^ m.iter().fold(Formula::T, |acc, &i| acc & Var(i))
+        });
+        assert!(Eq(Box::new(f), Box::new(g)).prove(9));
+        assert_eq!(anf.iter().map(|m| m.len()).max(), Some(3));
+    }
+
+    #[test]
+    fn affine() {
+        let f = And(
+            Box::new(Xor(var(0), Box::new(Xor(var(1), var(2))))),
+            Box::new(And(
+                Box::new(Eq(var(2), Box::new(Not(var(3))))),
+                Box::new(Or(var(1), var(4)))
+            ))
+        );
+        assert_eq!(f.extract_xor_clauses(), None);
+        assert_eq!(Xor(var(1), var(1)).extract_xor_clauses(), Some(vec![(vec![], true)]));
+        let f = And(
+            Box::new(Xor(var(0), Box::new(Xor(var(1), var(2))))),
+            Box::new(And(Box::new(Eq(var(2), Box::new(Not(var(3))))), Box::new(Eq(var(1), var(4)))))
+        );
+        assert_eq!(f.extract_xor_clauses(), Some(vec![
+            (vec![0, 1, 2], true),
+            (vec![2, 3], true),
+            (vec![1, 4], false),
+        ]));
+        for n in 5..8 {
+            assert_eq!(f.count_auto(n), f.count(n) as u128);
+        }
+        assert_eq!(f.count_auto(100), 1 << 97);
+
+        // Inconsistent clauses.
+        let g = And(Box::new(Xor(var(0), var(1))), Box::new(Eq(var(0), var(1))));
+        assert_eq!(g.count_auto(2), 0);
+
+        // Falls back to brute force.
+        let h = Or(var(0), var(1));
+        assert_eq!(h.extract_xor_clauses(), None);
+        assert_eq!(h.count_auto(2), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn affine_out_of_range() {
+        count_affine(2, &[(vec![0, 2], true)]);
+    }
+
+    #[test]
+    fn parse() {
+        let f: Formula = "(x0 & x1) => !x2 | T".parse().unwrap();
+        assert_eq!(f, Imply(
+            Box::new(And(var(0), var(1))),
+            Box::new(Or(Box::new(Not(var(2))), Box::new(Formula::T)))
+        ));
+        assert_eq!("x0 => x1 => x2".parse::<Formula>().unwrap(), Imply(var(0), Box::new(Imply(var(1), var(2)))));
+        assert_eq!("x0 ^ x1 == F".parse::<Formula>().unwrap(),
+            Eq(Box::new(Xor(var(0), var(1))), Box::new(Formula::F)));
+        assert!("x0 &".parse::<Formula>().is_err());
+        assert!("(x0".parse::<Formula>().is_err());
+        assert!("x0 x1".parse::<Formula>().is_err());
+        assert!("x1 2".parse::<Formula>().is_err());
+        assert!("x 1".parse::<Formula>().is_err());
+        #[cfg(not(feature = "quality"))]
+        assert!("~x0".parse::<Formula>().is_err());
+        assert!("x0 = > x1".parse::<Formula>().is_err());
+        assert!("x0 = = x1".parse::<Formula>().is_err());
+        assert_eq!(" ( x0\t&x1 ) ".parse::<Formula>().unwrap(), And(var(0), var(1)));
+    }
+
+    #[test]
+    fn display() {
+        let f = Imply(Box::new(And(var(0), var(1))), Box::new(Not(var(2))));
+        assert_eq!(f.to_string(), "(x0 & x1) => !x2");
+        assert_eq!(f.display(&["a", "b", "c"]).to_string(), "(a & b) => !c");
+        assert_eq!(f.display(&["a"]).to_string(), "(a & x1) => !x2");
+        assert_eq!(f.to_string().parse::<Formula>().unwrap(), f);
+        assert_eq!(Not(Box::new(Or(var(0), Box::new(Formula::T)))).to_string(), "!(x0 | T)");
+        assert_eq!(Qual(var(0), Box::new(Qubit(var(1)))).to_string(), "x0 ~~ ~x1");
+    }
+
+    #[test]
+    fn display_parse() {
+        let leaves = vec![Formula::T, Formula::F, Var(0), Var(1), Not(var(2))];
+        let ops = [And as fn(_, _) -> Formula, Or, Xor, Eq, Imply];
+        let mut fs = leaves.clone();
+        for op in &ops {
+            for a in &leaves {
+                for b in &leaves {fs.push(op(Box::new(a.clone()), Box::new(b.clone())))}
+            }
+        }
+        let small = fs.clone();
+        for op in &ops {
+            for a in &small {
+                for b in &small {fs.push(op(Box::new(a.clone()), Box::new(b.clone())))}
+            }
+            fs.push(Not(Box::new(op(var(0), var(1)))));
+        }
+        for f in &fs {
+            assert_eq!(f.to_string().parse::<Formula>(), Ok(f.clone()));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "quality")]
+    fn parse_quality() {
+        let fs = vec![
+            Qual(var(0), Box::new(Qubit(var(1)))),
+            Qubit(Box::new(Qubit(var(0)))),
+            Not(Box::new(Qual(Box::new(Eq(var(0), var(1))), Box::new(Qubit(Box::new(Not(var(2)))))))),
+            Qual(Box::new(Qual(var(0), var(1))), var(2)),
+        ];
+        for f in &fs {
+            assert_eq!(f.to_string().parse::<Formula>(), Ok(f.clone()));
+        }
+        assert_eq!("x0 == x1 ~~ x2".parse::<Formula>().unwrap(),
+            Qual(Box::new(Eq(var(0), var(1))), var(2)));
+    }
+
+    #[test]
+    fn simplify() {
+        assert_eq!(And(Box::new(Formula::T), var(0)).simplify(), Var(0));
+        assert_eq!(Not(Box::new(Not(var(0)))).simplify(), Var(0));
+        assert_eq!(Imply(Box::new(Formula::F), var(0)).simplify(), Formula::T);
+        assert_eq!(Or(var(1), var(1)).simplify(), Var(1));
+
+        let fs = vec![
+            Imply(Box::new(And(var(0), var(0))), Box::new(Not(Box::new(Not(var(1)))))),
+            Xor(Box::new(Or(var(0), Box::new(Formula::F))), var(0)),
+            Eq(Box::new(Imply(var(1), var(1))), Box::new(And(var(2), Box::new(Formula::T)))),
+            Or(Box::new(Not(Box::new(Formula::T))), Box::new(Xor(var(2), Box::new(Formula::T)))),
+        ];
+        for f in &fs {
+            let g = f.simplify();
+            assert!(g.size() < f.size());
+            assert_eq!(g.truth_table(3), f.truth_table(3));
+        }
+    }
+
+    #[test]
+    fn dyn_system() {
+        let sys = DynSystem {n: 3, rules: "(x0 => x1) & (x1 => x2)".parse().unwrap()};
+        assert!(sys.prove(&"x0 => x2".parse().unwrap()));
+        assert!(!sys.prove(&"x2 => x0".parse().unwrap()));
+        assert_eq!(sys.prob(&"x2".parse().unwrap()), Some(0.75));
+        assert_eq!(DynSystem {n: 1, rules: Formula::F}.prob(&Var(0)), None);
+    }
+}
@@ -0,0 +1,154 @@
+//! Import of propositions from the DIMACS CNF format.
+//!
+//! This is used to check the satisfiability of SAT benchmarks,
+//! complementing the `export` module.
+//!
+//! ```rust
+//! use pocket_prover::*;
+//! use pocket_prover::import::from_dimacs;
+//!
+//! let (n, mut f) = from_dimacs("p cnf 2 2\n1 2 0\n-1 0\n").unwrap();
+//! assert_eq!(countn(n, &mut f), 1);
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use core::fmt;
+
+use crate::{and, not, or, F, T};
+
+/// An error when parsing the DIMACS format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line of the error, starting at `1`.
+    pub line: usize,
+    /// Describes the error.
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        write!(w, "Line {}: {}", self.line, self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Parses clauses in the DIMACS format.
+///
+/// Returns the number of variables from the header `p cnf <variables> <clauses>`,
+/// and a function computing the AND of the clauses, reading variable `i` from argument `i - 1`.
+/// A negative number is a negated variable.
+/// Comment lines starting with `c` and blank lines are skipped,
+/// and clauses might span multiple lines, each ending in `0`.
+/// Parsing stops at a line starting with `%`, which ends the SATLIB benchmarks.
+pub fn from_dimacs(src: &str) -> Result<(usize, impl FnMut(&[u64]) -> u64), ParseError> {
+    let err = |line: usize, message: String| ParseError {line: line + 1, message};
+    let mut header: Option<(usize, usize)> = None;
+    let mut clauses: Vec<Vec<i64>> = vec![];
+    let mut clause = vec![];
+    for (i, line) in src.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') {continue}
+        if line.starts_with('%') {break}
+        if line.starts_with('p') {
+            if header.is_some() {return Err(err(i, "Expected only one header".into()))}
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.len() != 4 || words[1] != "cnf" {
+                return Err(err(i, "Expected header `p cnf <variables> <clauses>`".into()));
+            }
+            let num = |w: &str| w.parse::<usize>().map_err(|_| err(i, format!("Expected number, found `{}`", w)));
+            header = Some((num(words[2])?, num(words[3])?));
+            continue;
+        }
+        let (n, _) = header.ok_or_else(|| err(i, "Expected header before clauses".into()))?;
+        for word in line.split_whitespace() {
+            let lit: i64 = word.parse().map_err(|_| err(i, format!("Expected literal, found `{}`", word)))?;
+            if lit == 0 {
+                clauses.push(core::mem::take(&mut clause));
+            } else if lit.unsigned_abs() as usize > n {
+                return Err(err(i, format!("Variable `{}` is out of range", lit)));
+            } else {
+                clause.push(lit);
+            }
+        }
+    }
+    let line = src.lines().count();
+    let (n, m) = header.ok_or_else(|| err(line, "Expected header".into()))?;
+    if !clause.is_empty() {return Err(err(line, "Expected `0` at end of clause".into()))}
+    if clauses.len() != m {
+        return Err(err(line, format!("Expected {} clauses, found {}", m, clauses.len())));
+    }
+    Ok((n, move |vs: &[u64]| {
+        clauses.iter().fold(T, |acc, clause| and(acc, clause.iter().fold(F, |acc, &lit| {
+            let v = vs[lit.unsigned_abs() as usize - 1];
+            or(acc, if lit < 0 {not(v)} else {v})
+        })))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{and3, countn, proven};
+
+    #[test]
+    fn sat() {
+        let src = "c A satisfiable instance.\n\
+                   p cnf 3 3\n\
+                   1 -2 0\n\
+                   \n\
+                   2 3\n\
+                   0 -1 -3 0\n";
+        let (n, mut f) = from_dimacs(src).unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(countn(n, &mut f), countn(3, &mut |vs| {
+            and3(or(vs[0], not(vs[1])), or(vs[1], vs[2]), or(not(vs[0]), not(vs[2])))
+        }));
+        assert!(countn(n, &mut f) > 0);
+    }
+
+    #[test]
+    fn unsat() {
+        let (n, mut f) = from_dimacs("p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n").unwrap();
+        assert_eq!(countn(n, &mut f), 0);
+        assert!(proven(n, &mut |vs| not(f(vs))));
+    }
+
+    #[test]
+    fn satlib() {
+        let src = "c SATLIB instances end with a trailer.\n\
+                   p cnf 3 2\n \
+                   1 -2 3 0\n \
+                   -1 2 0\n\
+                   %\n\
+                   0\n\
+                   \n";
+        let (n, mut f) = from_dimacs(src).unwrap();
+        assert_eq!(countn(n, &mut f), countn(3, &mut |vs| {
+            and(or(vs[0], or(not(vs[1]), vs[2])), or(not(vs[0]), vs[1]))
+        }));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(from_dimacs("1 2 0\n").err().unwrap().line, 1);
+        assert_eq!(from_dimacs("p cnf 2 1\n1 3 0\n").err().unwrap().line, 2);
+        assert!(from_dimacs("p cnf 2 1\n1 2\n").is_err());
+        assert!(from_dimacs("p cnf 2 2\n1 2 0\n").is_err());
+        assert!(from_dimacs("p dnf 2 1\n1 2 0\n").is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        use crate::formula::Formula::*;
+
+        let f = Imply(Box::new(And(Box::new(Var(0)), Box::new(Var(1)))), Box::new(Not(Box::new(Var(2)))));
+        let (n, mut g) = from_dimacs(&crate::export::formula_to_dimacs(&f)).unwrap();
+        // Auxiliary variables are determined by the arguments of the formula.
+        assert_eq!(countn(n, &mut g), f.count(3));
+    }
+}
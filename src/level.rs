@@ -0,0 +1,114 @@
+//! Propositions tagged with a path level.
+//!
+//! In Path Semantics, propositions are stratified into levels,
+//! where reasoning relates a level only to the adjacent ones.
+//! A `Level<L>` is a proposition at level `L`,
+//! such that mixing levels incorrectly is a compile error.
+//!
+//! Propositions at the same level are combined using the operators `&`, `|`, `^` and `!`.
+//! Implication between levels is only allowed for adjacent levels (see `imply`).
+//!
+//! ```rust
+//! use pocket_prover::*;
+//! use pocket_prover::level::{self, Level};
+//!
+//! assert!(<(Level<0>, Level<1>)>::prove(|(a, b)| level::imply(a & !a, b)));
+//! ```
+//!
+//! Levels that are not adjacent are rejected:
+//!
+//! ```compile_fail
+//! use pocket_prover::level::{self, Level};
+//!
+//! let _ = level::imply(Level::<0>(pocket_prover::T), Level::<2>(pocket_prover::T));
+//! ```
+//!
+//! Levels can not be combined using operators:
+//!
+//! ```compile_fail
+//! use pocket_prover::level::Level;
+//!
+//! let _ = Level::<0>(pocket_prover::T) & Level::<1>(pocket_prover::T);
+//! ```
+
+use crate::{BaseSystem, Construct, CoreRules, T};
+
+/// A proposition at path level `L`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Level<const L: usize>(pub u64);
+
+impl<const L: usize> Construct for Level<L> {
+    fn construct(vs: &[u64]) -> Self {Level(vs[0])}
+    fn n() -> usize {1}
+}
+
+impl<const L: usize> CoreRules for Level<L> {
+    fn core_rules(&self) -> u64 {T}
+}
+
+impl<const L: usize> BaseSystem for Level<L> {}
+
+impl<const L: usize> From<Level<L>> for u64 {
+    fn from(a: Level<L>) -> u64 {a.0}
+}
+
+impl<const L: usize> core::ops::Not for Level<L> {
+    type Output = Level<L>;
+    fn not(self) -> Level<L> {Level(crate::not(self.0))}
+}
+
+impl<const L: usize> core::ops::BitAnd for Level<L> {
+    type Output = Level<L>;
+    fn bitand(self, other: Level<L>) -> Level<L> {Level(crate::and(self.0, other.0))}
+}
+
+impl<const L: usize> core::ops::BitOr for Level<L> {
+    type Output = Level<L>;
+    fn bitor(self, other: Level<L>) -> Level<L> {Level(crate::or(self.0, other.0))}
+}
+
+impl<const L: usize> core::ops::BitXor for Level<L> {
+    type Output = Level<L>;
+    fn bitxor(self, other: Level<L>) -> Level<L> {Level(crate::xor(self.0, other.0))}
+}
+
+/// Implemented for pairs of levels that are adjacent.
+///
+/// Levels `L` and `L + 1` are adjacent in both directions, for levels up to 16.
+pub trait Adjacent<Rhs> {}
+
+macro_rules! adjacent(
+    ($($a:expr => $b:expr),* $(,)?) => {
+        $(
+            impl Adjacent<Level<$b>> for Level<$a> {}
+            impl Adjacent<Level<$a>> for Level<$b> {}
+        )*
+    };
+);
+
+adjacent!(
+    0 => 1, 1 => 2, 2 => 3, 3 => 4, 4 => 5, 5 => 6, 6 => 7, 7 => 8,
+    8 => 9, 9 => 10, 10 => 11, 11 => 12, 12 => 13, 13 => 14, 14 => 15, 15 => 16,
+);
+
+/// Implication between propositions at adjacent levels.
+pub fn imply<A, B>(a: A, b: B) -> u64
+    where A: Adjacent<B> + Into<u64>, B: Into<u64>
+{
+    crate::imply(a.into(), b.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Prove;
+
+    #[test]
+    fn levels() {
+        assert_eq!(<Level<3> as Construct>::n(), 1);
+        assert!(<(Level<0>, Level<1>)>::prove(|(a, b)| imply(a & !a, b)));
+        assert!(!<(Level<0>, Level<1>)>::prove(|(a, b)| imply(a, b)));
+        assert!(<(Level<1>, Level<0>)>::prove(|(a, b)| imply(b, a | !a)));
+        assert!(<(Level<2>, Level<2>)>::prove(|(a, b)| u64::from((a ^ b) ^ b ^ !a)));
+    }
+}
@@ -1,4 +1,5 @@
 #![deny(missing_docs)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 //! # Pocket-Prover
 //! A fast, brute force, automatic theorem prover for first order logic
@@ -79,8 +80,11 @@
 //! such that the core axiom of [Path Semantics](https://github.com/advancedresearch/path_semantics)
 //! is satisfied.
 //!
-//! This library has currently only support for level 1 and 0.
-//! These functions are prefixed with `path1_`.
+//! This library has currently support for level 1 and 0,
+//! with functions prefixed with `path1_`.
+//! Level 2, 1 and 0 are supported by `path2_countnm`, `path2_provenm` and `path2_prove!`,
+//! where each pair of neighbouring levels is related like level 1 and 0.
+//! Any number of levels is supported by `pathn_countnm` and `pathn_provenm`.
 //!
 //! The macros `count!` and `prove!` will automatically expand
 //! to `path1_count!` and `path1_prove!`.
@@ -150,6 +154,8 @@
 //! ```rust
 //! use pocket_prover::*;
 //!
+//! # #[cfg(not(feature = "quality"))] fn main() {}
+//! # #[cfg(feature = "quality")]
 //! fn main() {
 //!     println!("Path semantics: {}", measure(1, || prove!(&mut |a, b, c, d| {
 //!         imply(
@@ -163,15 +169,55 @@
 //!     })));
 //! }
 //! ```
+//!
+//! Path Semantical Quality requires the `quality` feature, which is enabled by default.
+//!
+//! ### Features
+//!
+//! - `std` (default): Uses the standard library, otherwise the crate is `no_std` with `alloc`
+//! - `quality` (default): Qubits and Path Semantical Quality, using `rand`
+//!
+//! With `default-features = false`, the propositional core (e.g. `and`, `count1..count10`,
+//! `proven`, the `Prove` and `Construct` traits and the macros) is available without `std`.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+#[cfg(feature = "quality")]
+use core::cell::Cell;
+#[cfg(feature = "quality")]
+use std::thread::LocalKey;
 
 pub mod extract;
+pub mod formula;
+pub mod expr;
+pub mod circuit;
+pub mod level;
+pub mod cardinality;
+pub mod export;
+pub mod import;
+#[cfg(feature = "external-solver")]
+pub mod external;
+#[cfg(feature = "bench")]
+pub mod bench;
 
+#[cfg(feature = "quality")]
 pub use qual as q;
+#[cfg(feature = "quality")]
 pub use qubit as qu;
+#[cfg(feature = "quality")]
 pub use aqual as aq;
+#[cfg(feature = "quality")]
 pub use contra_qual as cq;
+#[cfg(feature = "quality")]
 pub use platonic_qubit as pqu;
+#[cfg(feature = "quality")]
 pub use amplify as amp;
+pub use eq as iff;
 
 /// An AND relation of variable arguments.
 #[macro_export]
@@ -298,6 +344,120 @@ macro_rules! xor(
     };
 );
 
+/// A NAND relation of variable arguments.
+#[macro_export]
+macro_rules! nand(
+    ($x0:expr $(,)?) => {not($x0)};
+    ($x0:expr, $x1:expr $(,)?) => {
+        nand($x0, $x1)
+    };
+    ($x0:expr, $x1:expr, $x2:expr $(,)?) => {
+        nand3($x0, $x1, $x2)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr $(,)?) => {
+        nand4($x0, $x1, $x2, $x3)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr $(,)?) => {
+        nand5($x0, $x1, $x2, $x3, $x4)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr, $x5:expr $(,)?) => {
+        nand6($x0, $x1, $x2, $x3, $x4, $x5)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr, $x5:expr, $x6:expr $(,)?) => {
+        nand7($x0, $x1, $x2, $x3, $x4, $x5, $x6)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr, $x5:expr, $x6:expr, $x7:expr $(,)?) => {
+        nand8($x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr,
+     $x5:expr, $x6:expr, $x7:expr, $x8:expr $(,)?) => {
+        nand9($x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr,
+     $x5:expr, $x6:expr, $x7:expr, $x8:expr, $x9:expr $(,)?) => {
+        nand10($x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9)
+    };
+    ($($x:expr),+ $(,)?) => {
+        not(and!($($x),+))
+    };
+);
+
+/// A NOR relation of variable arguments.
+#[macro_export]
+macro_rules! nor(
+    ($x0:expr $(,)?) => {not($x0)};
+    ($x0:expr, $x1:expr $(,)?) => {
+        nor($x0, $x1)
+    };
+    ($x0:expr, $x1:expr, $x2:expr $(,)?) => {
+        nor3($x0, $x1, $x2)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr $(,)?) => {
+        nor4($x0, $x1, $x2, $x3)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr $(,)?) => {
+        nor5($x0, $x1, $x2, $x3, $x4)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr, $x5:expr $(,)?) => {
+        nor6($x0, $x1, $x2, $x3, $x4, $x5)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr, $x5:expr, $x6:expr $(,)?) => {
+        nor7($x0, $x1, $x2, $x3, $x4, $x5, $x6)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr, $x5:expr, $x6:expr, $x7:expr $(,)?) => {
+        nor8($x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr,
+     $x5:expr, $x6:expr, $x7:expr, $x8:expr $(,)?) => {
+        nor9($x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr,
+     $x5:expr, $x6:expr, $x7:expr, $x8:expr, $x9:expr $(,)?) => {
+        nor10($x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9)
+    };
+    ($($x:expr),+ $(,)?) => {
+        not(or!($($x),+))
+    };
+);
+
+/// An XNOR relation of variable arguments.
+#[macro_export]
+macro_rules! xnor(
+    ($x0:expr $(,)?) => {not($x0)};
+    ($x0:expr, $x1:expr $(,)?) => {
+        xnor($x0, $x1)
+    };
+    ($x0:expr, $x1:expr, $x2:expr $(,)?) => {
+        xnor3($x0, $x1, $x2)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr $(,)?) => {
+        xnor4($x0, $x1, $x2, $x3)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr $(,)?) => {
+        xnor5($x0, $x1, $x2, $x3, $x4)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr, $x5:expr $(,)?) => {
+        xnor6($x0, $x1, $x2, $x3, $x4, $x5)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr, $x5:expr, $x6:expr $(,)?) => {
+        xnor7($x0, $x1, $x2, $x3, $x4, $x5, $x6)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr, $x5:expr, $x6:expr, $x7:expr $(,)?) => {
+        xnor8($x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr,
+     $x5:expr, $x6:expr, $x7:expr, $x8:expr $(,)?) => {
+        xnor9($x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $x3:expr, $x4:expr,
+     $x5:expr, $x6:expr, $x7:expr, $x8:expr, $x9:expr $(,)?) => {
+        xnor10($x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9)
+    };
+    ($($x:expr),+ $(,)?) => {
+        not(xor!($($x),+))
+    };
+);
+
 /// An IMPLY chain of variable arguments.
 #[macro_export]
 macro_rules! imply(
@@ -336,6 +496,19 @@ macro_rules! imply(
     };
 );
 
+/// A MAJORITY relation of 3 or more arguments.
+///
+/// Returns `true` when strictly more than half of the arguments are `true`.
+#[macro_export]
+macro_rules! maj(
+    ($x0:expr, $x1:expr, $x2:expr $(,)?) => {
+        maj($x0, $x1, $x2)
+    };
+    ($x0:expr, $x1:expr, $x2:expr, $($y:expr),+ $(,)?) => {
+        majn(&[$x0, $x1, $x2, $($y),+])
+    };
+);
+
 /// Counts the number of solutions of a variable argument boolean function.
 ///
 /// Expands automatically to Path Semantical Logic when using tuples as arguments.
@@ -375,6 +548,34 @@ macro_rules! count(
            $x5:ident, $x6:ident, $x7:ident, $x8:ident, $x9:ident $(,)?| $e:expr) => {
         count10(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9| $e)
     };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident $(,)?| $e:expr) => {
+        count11(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident, $x11:ident $(,)?| $e:expr) => {
+        count12(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10, $x11| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident, $x11:ident,
+           $x12:ident $(,)?| $e:expr) => {
+        count13(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10, $x11, $x12| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident, $x11:ident,
+           $x12:ident, $x13:ident $(,)?| $e:expr) => {
+        count14(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10, $x11, $x12, $x13| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident, $x11:ident,
+           $x12:ident, $x13:ident, $x14:ident $(,)?| $e:expr) => {
+        count15(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10, $x11, $x12, $x13, $x14| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident, $x11:ident,
+           $x12:ident, $x13:ident, $x14:ident, $x15:ident $(,)?| $e:expr) => {
+        count16(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10, $x11, $x12, $x13, $x14, $x15| $e)
+    };
     (&mut |$($x:ident),+ $(,)?| $e:expr) => {
         countn(tup_count!($($x),+), &mut |x| {
             tup_set!(x, ($($x),+));
@@ -406,6 +607,27 @@ macro_rules! tup_set(
     };
 );
 
+/// Creates a proposition that can be stored, combined and proved later.
+///
+/// The named arguments are mapped to indices of the argument slice in order,
+/// such that the result can be proved using e.g. `proven`.
+///
+/// ```rust
+/// use pocket_prover::*;
+///
+/// let modus_ponens = formula!(|a, b| imply(and(a, imply(a, b)), b));
+/// assert!(proven(2, &mut |vs| modus_ponens(vs)));
+/// ```
+#[macro_export]
+macro_rules! formula(
+    (|$($x:ident),+ $(,)?| $e:expr) => {
+        Box::new(move |_vs: &[u64]| -> u64 {
+            tup_set!(_vs, ($($x),+));
+            $e
+        }) as Box<dyn Fn(&[u64]) -> u64>
+    };
+);
+
 /// Path Semantical Logic: Counts the number of solutions of a variable argument boolean function.
 #[macro_export]
 macro_rules! path1_count(
@@ -491,6 +713,34 @@ macro_rules! prove(
            $x5:ident, $x6:ident, $x7:ident, $x8:ident, $x9:ident $(,)?| $e:expr) => {
         prove10(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9| $e)
     };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident $(,)?| $e:expr) => {
+        prove11(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident, $x11:ident $(,)?| $e:expr) => {
+        prove12(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10, $x11| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident, $x11:ident,
+           $x12:ident $(,)?| $e:expr) => {
+        prove13(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10, $x11, $x12| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident, $x11:ident,
+           $x12:ident, $x13:ident $(,)?| $e:expr) => {
+        prove14(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10, $x11, $x12, $x13| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident, $x11:ident,
+           $x12:ident, $x13:ident, $x14:ident $(,)?| $e:expr) => {
+        prove15(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10, $x11, $x12, $x13, $x14| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident,
+           $x6:ident, $x7:ident, $x8:ident, $x9:ident, $x10:ident, $x11:ident,
+           $x12:ident, $x13:ident, $x14:ident, $x15:ident $(,)?| $e:expr) => {
+        prove16(&mut |$x0, $x1, $x2, $x3, $x4, $x5, $x6, $x7, $x8, $x9, $x10, $x11, $x12, $x13, $x14, $x15| $e)
+    };
     (&mut |$($x:ident),+ $(,)?| $e:expr) => {
         proven(tup_count!($($x),+), &mut |x| {
             tup_set!(x, ($($x),+));
@@ -502,6 +752,98 @@ macro_rules! prove(
     };
 );
 
+/// Returns `Ok(())` if proposition is correct, otherwise the first counterexample.
+///
+/// Same as `prove!`, but without Path Semantical Logic (see `proven_ce`).
+#[macro_export]
+macro_rules! prove_ce(
+    (&mut |$x0:ident $(,)?| $e:expr) => {
+        prove_ce1(&mut |$x0| $e)
+    };
+    (&mut |$x0:ident, $x1:ident $(,)?| $e:expr) => {
+        prove_ce2(&mut |$x0, $x1| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident $(,)?| $e:expr) => {
+        prove_ce3(&mut |$x0, $x1, $x2| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident $(,)?| $e:expr) => {
+        prove_ce4(&mut |$x0, $x1, $x2, $x3| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident $(,)?| $e:expr) => {
+        prove_ce5(&mut |$x0, $x1, $x2, $x3, $x4| $e)
+    };
+    (&mut |$x0:ident, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident $(,)?| $e:expr) => {
+        prove_ce6(&mut |$x0, $x1, $x2, $x3, $x4, $x5| $e)
+    };
+    (&mut |$($x:ident),+ $(,)?| $e:expr) => {
+        proven_ce(tup_count!($($x),+), &mut |x| {
+            tup_set!(x, ($($x),+));
+            $e
+        })
+    };
+);
+
+/// Returns `true` if a statement about a logical system is correct according to its rules.
+///
+/// Takes the system type with the fields used by the statement,
+/// e.g. `prove_sys!(Foo {a, b}, imply(a, b))` is the same as
+/// `<Foo as Prove>::prove(|x| {let Foo {a, b, ..} = x; imply(a, b)})`.
+/// A macro can not look up the fields of a type, so the used fields are listed,
+/// and they must be visible where the macro is used.
+///
+/// ```rust
+/// use pocket_prover::*;
+///
+/// #[derive(Copy, Clone)]
+/// pub struct Bar {
+///     pub a: u64,
+///     pub b: u64,
+/// }
+///
+/// impl Construct for Bar {
+///     fn construct(vs: &[u64]) -> Self {Bar {a: vs[0], b: vs[1]}}
+/// }
+///
+/// impl CoreRules for Bar {
+///     fn core_rules(&self) -> u64 {imply(self.a, self.b)}
+/// }
+///
+/// impl BaseSystem for Bar {}
+///
+/// #[derive(Copy, Clone)]
+/// pub struct Foo<T = ()> {
+///     pub bar: T,
+///     pub c: u64,
+/// }
+///
+/// impl Construct for Foo<Bar> {
+///     fn construct(vs: &[u64]) -> Self {Foo {bar: Construct::construct(vs), c: vs[2]}}
+/// }
+///
+/// impl CoreRules for Foo<Bar> {
+///     fn core_rules(&self) -> u64 {imply(self.bar.b, self.c)}
+/// }
+///
+/// impl ExtendRules for Foo<Bar> {
+///     type Inner = Bar;
+///     fn inner(&self) -> &Bar {&self.bar}
+///     fn extend_rules(&self, _: &Bar) -> u64 {T}
+/// }
+///
+/// assert!(prove_sys!(Bar {a, b}, imply(a, b)));
+/// assert!(!prove_sys!(Bar {a, b}, imply(b, a)));
+/// assert!(prove_sys!(Foo<Bar> {bar, c}, imply(bar.a, c)));
+/// ```
+#[macro_export]
+macro_rules! prove_sys(
+    ($sys:ident $(<$($g:ty),+>)? {$($field:ident),* $(,)?}, $e:expr $(,)?) => {
+        <$sys $(<$($g),+>)? as Prove>::prove(|x| {
+            let $sys {$($field,)* ..} = x;
+            $e
+        })
+    };
+);
+
 /// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
 #[macro_export]
 macro_rules! path1_prove(
@@ -548,16 +890,93 @@ macro_rules! path1_prove(
     };
 );
 
+/// Path Semantical Logic: Proves a proposition with three levels.
+///
+/// Takes three tuples of propositions at level 2, 1 and 0.
+/// For more information, see `path2_countnm`.
+///
+/// ```rust
+/// use pocket_prover::*;
+///
+/// assert!(path2_prove!(&mut |(f, g, h), (a, b), (x,)| {
+///     imply(and!(imply(f, a), imply(g, b), imply(h, x)), imply(f, a))
+/// }));
+/// ```
+#[macro_export]
+macro_rules! path2_prove(
+    (&mut |($($x:ident),+ $(,)?), ($($y:ident),+ $(,)?), ($($z:ident),+ $(,)?)| $e:expr) => {
+        path2_provenm(tup_count!($($x),+), tup_count!($($y),+), tup_count!($($z),+), &mut |f, g, x| {
+            tup_set!(f, ($($x),+));
+            tup_set!(g, ($($y),+));
+            tup_set!(x, ($($z),+));
+            $e
+        })
+    };
+);
+
+/// Proves a proposition and prints the result after a label.
+///
+/// Accepts the same arguments as `prove!`, including Path Semantical Logic.
+/// Prints `<label>: <result>` (see `report_line`) and returns the result.
+///
+/// ```rust
+/// use pocket_prover::*;
+///
+/// assert!(report!("Modus ponens", &mut |a, b| imply(and(a, imply(a, b)), b)));
+/// ```
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! report(
+    ($label:expr, $($t:tt)+) => {{
+        let res = prove!($($t)+);
+        println!("{}", report_line($label, res));
+        res
+    }};
+);
+
+/// Proves a proposition repeatedly and prints the result after a label.
+///
+/// This is used for Path Semantical Quantum Propositional Logic (see `measure`).
+/// Accepts the number of rounds followed by the same arguments as `prove!`.
+///
+/// Requires the `quality` feature.
+#[cfg(feature = "quality")]
+#[macro_export]
+macro_rules! report_measure(
+    ($label:expr, $rounds:expr, $($t:tt)+) => {{
+        let res = measure($rounds, || prove!($($t)+));
+        println!("{}", report_line($label, res));
+        res
+    }};
+);
+
 /// Path Semantical Logic: A contractible "family of types".
 ///
 /// All propositions are either `true` or all propositions are `false`.
+/// This is the same as `iff!` and `alleq`.
 #[macro_export]
 macro_rules! contr(
     ($($x:expr),*) => {
+        // Both sides can not be true at the same time, so `xor` is the same as `or` in `alleq`.
         xor(and!($($x),*), and!($(not($x)),*))
     }
 );
 
+/// An IFF relation of variable arguments.
+///
+/// Returns `true` when all arguments are equal (see `alleq`).
+#[macro_export]
+macro_rules! iff(
+    ($x0:expr, $x1:expr $(,)?) => {
+        iff($x0, $x1)
+    };
+    ($($x:expr),+ $(,)?) => {
+        alleq(&[$($x),+])
+    };
+);
+
 /// The False proposition.
 /// Used to alternate higher than 6 arguments, set to `0`.
 pub const F: u64 = 0b00000000_00000000_00000000_00000000_00000000_00000000_00000000_00000000;
@@ -591,23 +1010,56 @@ impl Observable for bool {
 }
 
 impl Observable for u64 {
-    fn max_energy() -> u64 {std::u64::MAX}
+    fn max_energy() -> u64 {core::u64::MAX}
     fn min_energy(self, other: u64) -> u64 {self.min(other)}
 }
 
+/// Probabilities in `[0, 1]`.
+impl Observable for f64 {
+    fn max_energy() -> f64 {1.0}
+    fn min_energy(self, other: f64) -> f64 {self.min(other)}
+}
+
+/// An interval `[lo, hi]` observed as a bound.
+///
+/// Measuring intervals gives their intersection.
+/// The intersection of disjoint intervals is empty, where `lo > hi`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Interval {
+    /// The lower bound.
+    pub lo: f64,
+    /// The upper bound.
+    pub hi: f64,
+}
+
+impl Interval {
+    /// Returns `true` if the interval contains no numbers.
+    pub fn is_empty(&self) -> bool {self.lo > self.hi}
+}
+
+impl Observable for Interval {
+    fn max_energy() -> Interval {
+        Interval {lo: f64::NEG_INFINITY, hi: f64::INFINITY}
+    }
+    fn min_energy(self, other: Interval) -> Interval {
+        Interval {lo: self.lo.max(other.lo), hi: self.hi.min(other.hi)}
+    }
+}
+
 /// Removes Sesh property from a proposition.
 ///
 /// Sesh is the property that `!~a == ~!a`, which holds for `qubit` (`~`).
 /// Sometimes you want to get rid of this property (see `platonic_qubit`).
 ///
 /// For more information, see [paper "Un- and Re-Sesh"](https://github.com/advancedresearch/path_semantics/blob/master/papers-wip2/un-and-re-sesh.pdf).
+#[cfg(feature = "quality")]
 pub fn un_sesh(a: u64) -> u64 {
     use rand::{Rng, SeedableRng};
     use rand::rngs::StdRng;
 
-    let r = unsafe {&*current::Current::<u64>::new()};
-    let bit = (*r % 64) as u8;
-    let mut rng2 = StdRng::seed_from_u64(*r);
+    let r = round_ctx().round;
+    let bit = (r % 64) as u8;
+    let mut rng2 = StdRng::seed_from_u64(r);
     let pat: u64 = rng2.gen();
     let pat2: u64 = rng2.gen();
     let pat2 = if (pat >> bit) & 1 != (pat2 >> bit) & 1 {not(pat2)} else {pat2};
@@ -621,13 +1073,14 @@ pub fn un_sesh(a: u64) -> u64 {
 /// `re_sesh . un_sesh <=> id`.
 ///
 /// For more information, see [paper "Un- and Re-Sesh"](https://github.com/advancedresearch/path_semantics/blob/master/papers-wip2/un-and-re-sesh.pdf).
+#[cfg(feature = "quality")]
 pub fn re_sesh(a: u64) -> u64 {
     use rand::{Rng, SeedableRng};
     use rand::rngs::StdRng;
 
-    let r = unsafe {&*current::Current::<u64>::new()};
-    let bit = (*r % 64) as u8;
-    let mut rng2 = StdRng::seed_from_u64(*r);
+    let r = round_ctx().round;
+    let bit = (r % 64) as u8;
+    let mut rng2 = StdRng::seed_from_u64(r);
     let pat: u64 = rng2.gen();
     let pat2: u64 = rng2.gen();
     let pat2 = if (pat >> bit) & 1 != (pat2 >> bit) & 1 {not(pat2)} else {pat2};
@@ -642,87 +1095,300 @@ pub fn re_sesh(a: u64) -> u64 {
 /// Functions like `qubit`, but without the Sesh property `!~a == ~!a`.
 ///
 /// For more information, see [paper](https://github.com/advancedresearch/path_semantics/blob/master/papers-wip2/un-and-re-sesh.pdf).
+#[cfg(feature = "quality")]
 pub fn platonic_qubit(a: u64) -> u64 {un_sesh(qubit(a))}
 
-/// Prepares a qubit using a proposition as seed.
-pub fn qubit(a: u64) -> u64 {
-    use rand::{Rng, SeedableRng};
-    use rand::rngs::StdRng;
-    let r = unsafe {&*current::Current::<u64>::new()};
-    if a & 1 == 1 {
-        let mut rng = StdRng::seed_from_u64(not(a) ^ *r);
-        not(rng.gen())
-    } else {
-        let mut rng = StdRng::seed_from_u64(a ^ *r);
-        rng.gen()
-    }
+#[cfg(feature = "quality")]
+thread_local! {
+    /// Stores the context of the current round, set while counting or proving.
+    static ROUND: Cell<Option<QubitCtx>> = const {Cell::new(None)};
+    /// Stores a fixed seed for rounds (see `with_round_seed`).
+    static ROUND_SEED: Cell<Option<u64>> = const {Cell::new(None)};
+    /// Stores the namespace of qubits.
+    static QUBIT_NAMESPACE: Cell<u64> = const {Cell::new(0)};
+    /// Stores whether qubits are whitened.
+    static QUBIT_WHITENING: Cell<bool> = const {Cell::new(false)};
 }
 
-/// Amplify a "wavefunction" of a proposition using its qubit transform.
-pub fn amplify(n: u32, mut a: u64) -> u64 {
-    for _ in 0..n {
-        a |= qubit(a);
-    }
-    a
-}
+/// Sets a thread local value while running a closure.
+///
+/// The previous value is restored when the closure returns or unwinds.
+#[cfg(feature = "quality")]
+fn with_local<T: Copy + 'static, R>(
+    key: &'static LocalKey<Cell<T>>,
+    val: T,
+    fun: impl FnOnce() -> R
+) -> R {
+    struct Restore<T: Copy + 'static>(&'static LocalKey<Cell<T>>, T);
 
-/// Path semantical continuous map `a ~> b`.
-pub fn cont(a: u64, b: u64) -> u64 {and(imply(a, b), imply(qubit(a), qubit(b)))}
+    impl<T: Copy + 'static> Drop for Restore<T> {
+        fn drop(&mut self) {self.0.with(|x| x.set(self.1))}
+    }
 
-/// Path semantical quality `a ~~ b`.
-pub fn qual(a: u64, b: u64) -> u64 {and!(eq(a, b), qubit(a), qubit(b))}
+    let _restore = Restore(key, key.with(|x| x.replace(val)));
+    fun()
+}
 
-/// Path semantical aquality `a ~¬~ b`.
+/// Runs a closure where qubits are prepared in a namespace.
 ///
-/// For more information, see [paper](https://github.com/advancedresearch/path_semantics/blob/master/papers-wip2/path-semantical-aquality.pdf).
-pub fn aqual(a: u64, b: u64) -> u64 {and!(eq(a, b), qubit(not(a)), qubit(not(b)))}
+/// The namespace is mixed into the seed of `qubit`,
+/// such that qubits of independent experiments in the same round are decorrelated.
+/// This also holds for `qual`/`q` and other functions using `qubit`.
+/// Qubits in the same namespace and round are the same.
+/// The default namespace is `0`.
+#[cfg(feature = "quality")]
+pub fn with_qubit_namespace<R>(ns: u64, fun: impl FnOnce() -> R) -> R {
+    let ctx = QubitCtx::current().map(|ctx| ctx.with_namespace(ns));
+    with_local(&QUBIT_NAMESPACE, ns, || with_local(&ROUND, ctx, fun))
+}
 
-/// Path semantical contravariant quality `a ¬~~ b`.
+/// Runs a closure where the seeds of qubits are whitened or not.
 ///
-/// For more information, see [paper](https://github.com/advancedresearch/path_semantics/blob/master/papers-wip2/path-semantical-contravariant-quality.pdf).
-pub fn contra_qual(a: u64, b: u64) -> u64 {
-    and!(eq(a, b), not(qubit(a)), qubit(b))
+/// Whitening mixes the seed with a few rounds of splitmix64 before generating the qubit.
+/// This reduces correlation between qubits of similar seeds,
+/// which matters for statistically demanding experiments.
+///
+/// Whitening changes which qubit is prepared for a seed,
+/// so results are not reproducible between whitened and unwhitened runs.
+/// Within a round, whitened qubits are the same for the same seed.
+/// By default, qubits are not whitened.
+#[cfg(feature = "quality")]
+pub fn with_qubit_whitening<R>(on: bool, fun: impl FnOnce() -> R) -> R {
+    let ctx = QubitCtx::current().map(|ctx| ctx.with_whitening(on));
+    with_local(&QUBIT_WHITENING, on, || with_local(&ROUND, ctx, fun))
 }
 
-/// Path semantical function `f_n(a, b)` up to some homotopy level `n`.
-pub fn hom_f(f: fn(u64, u64) -> u64, n: u32, mut a: u64, mut b: u64) -> u64 {
-    let mut res = T;
-    for _ in 0..n {
-        res = and(res, f(a, b));
-        a = qubit(a);
-        b = qubit(b);
-    }
-    res
+/// The splitmix64 mixing function.
+#[cfg(feature = "quality")]
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
 }
 
-/// Aligns implication of qubits up to some homotopy level.
+/// Generates the random bits of qubits from a seed (see `qubit_with`).
 ///
-/// This is also path semantical continuous map `a ~>_n b` up to homotopy level `n`.
-pub fn hom_imply(n: u32, a: u64, b: u64) -> u64 {
-    hom_f(imply, n, a, b)
+/// The same seed must give the same bits, since qubits are the same for the same seed in a round.
+#[cfg(feature = "quality")]
+pub trait Entropy {
+    /// Generates random bits from a seed.
+    fn sample(&mut self, seed: u64) -> u64;
 }
 
-/// Aligns equality of qubits up to some homotopy level.
-pub fn hom_eq(n: u32, a: u64, b: u64) -> u64 {
-    hom_f(eq, n, a, b)
-}
+/// The default entropy of qubits, using `StdRng` seeded from the seed.
+#[cfg(feature = "quality")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StdRngEntropy;
 
-/// Aligns logical AND of qubits up to some homotopy level.
-pub fn hom_and(n: u32, a: u64, b: u64) -> u64 {
-    hom_f(and, n, a, b)
-}
+#[cfg(feature = "quality")]
+impl Entropy for StdRngEntropy {
+    fn sample(&mut self, seed: u64) -> u64 {
+        use rand::{Rng, SeedableRng};
+        use rand::rngs::StdRng;
 
-/// Aligns logical OR of qubits up to some homotopy level.
-pub fn hom_or(n: u32, a: u64, b: u64) -> u64 {
-    hom_f(or, n, a, b)
+        StdRng::seed_from_u64(seed).gen()
+    }
 }
 
-/// Aligns logical XOR of qubits up to some homotopy level.
-pub fn hom_xor(n: u32, a: u64, b: u64) -> u64 {
-    hom_f(xor, n, a, b)
+/// The context of a round, used to prepare qubits without reading the current round.
+///
+/// Qubits prepared with the same context are the same, on any thread,
+/// e.g. `QubitCtx::new(7).qubit(a)` is `qubit(a)` in a round with seed `7`
+/// (see `with_round_seed`).
+/// Use `measure_ctx` to get a new context every measurement.
+#[cfg(feature = "quality")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct QubitCtx {
+    /// The seed of the round.
+    pub round: u64,
+    /// The namespace of qubits (see `with_qubit_namespace`).
+    pub namespace: u64,
+    /// Whether qubits are whitened (see `with_qubit_whitening`).
+    pub whitening: bool,
+}
+
+#[cfg(feature = "quality")]
+impl QubitCtx {
+    /// Creates a new context of a round, using the default namespace without whitening.
+    pub fn new(round: u64) -> QubitCtx {QubitCtx {round, namespace: 0, whitening: false}}
+
+    /// Sets the namespace of qubits.
+    pub fn with_namespace(self, namespace: u64) -> QubitCtx {QubitCtx {namespace, ..self}}
+
+    /// Sets whether qubits are whitened.
+    pub fn with_whitening(self, whitening: bool) -> QubitCtx {QubitCtx {whitening, ..self}}
+
+    /// Gets the context of the current round, or `None` when called outside a round.
+    ///
+    /// The context is created by the round, using the current namespace and whitening.
+    pub fn current() -> Option<QubitCtx> {ROUND.with(|x| x.get())}
+
+    /// Creates a new context of a round, using the current namespace and whitening.
+    fn scoped(round: u64) -> QubitCtx {
+        QubitCtx {
+            round,
+            namespace: QUBIT_NAMESPACE.with(|x| x.get()),
+            whitening: QUBIT_WHITENING.with(|x| x.get()),
+        }
+    }
+
+    /// Prepares a qubit using a proposition as seed, same as `qubit`.
+    pub fn qubit(&self, a: u64) -> u64 {self.qubit_with(&mut StdRngEntropy, a)}
+
+    /// Prepares a qubit using a proposition as seed, same as `qubit_with`.
+    pub fn qubit_with<E: Entropy>(&self, e: &mut E, a: u64) -> u64 {
+        let r = self.round ^ self.namespace;
+        let seed = |x: u64| if self.whitening {splitmix64(splitmix64(x))} else {x};
+        if a & 1 == 1 {
+            not(e.sample(seed(not(a) ^ r)))
+        } else {
+            e.sample(seed(a ^ r))
+        }
+    }
+}
+
+/// Gets the context of the current round, panicking outside a round.
+#[cfg(feature = "quality")]
+fn round_ctx() -> QubitCtx {
+    QubitCtx::current().unwrap_or_else(|| panic!("{}", SolveError::NoRound))
+}
+
+/// Prepares a qubit using a proposition as seed.
+///
+/// The seed is mixed with the round and the namespace (see `with_qubit_namespace`),
+/// and optionally whitened (see `with_qubit_whitening`).
+///
+/// The round is only set while counting or proving, e.g. inside `countn` or `prove!`.
+/// Panics when called outside a round, use `try_qubit` to get an error instead,
+/// or `QubitCtx` to prepare qubits of an explicit round.
+#[cfg(feature = "quality")]
+pub fn qubit(a: u64) -> u64 {qubit_with(&mut StdRngEntropy, a)}
+
+/// Prepares a qubit using a proposition as seed, generating the bits from an entropy source.
+///
+/// This is the same as `qubit` when using `StdRngEntropy`.
+/// A counter based entropy source gives the same qubits across platforms.
+#[cfg(feature = "quality")]
+pub fn qubit_with<E: Entropy>(e: &mut E, a: u64) -> u64 {round_ctx().qubit_with(e, a)}
+
+/// Prepares a qubit using a proposition as seed, same as `qubit`.
+///
+/// Returns an error when called outside a round instead of panicking.
+#[cfg(feature = "quality")]
+pub fn try_qubit(a: u64) -> Result<u64, SolveError> {
+    QubitCtx::current().map(|ctx| ctx.qubit(a)).ok_or(SolveError::NoRound)
+}
+
+/// Amplify a "wavefunction" of a proposition using its qubit transform.
+#[cfg(feature = "quality")]
+pub fn amplify(n: u32, mut a: u64) -> u64 {
+    for _ in 0..n {
+        a |= qubit(a);
+    }
+    a
+}
+
+/// Amplifies a proposition like `amplify`, stopping when a round adds no new bits.
+///
+/// Returns the amplified proposition and the number of rounds that added bits,
+/// such that `amplify(rounds, a)` gives the same proposition.
+/// At most `max` rounds are used.
+#[cfg(feature = "quality")]
+pub fn amplify_until_stable(mut a: u64, max: u32) -> (u64, u32) {
+    for i in 0..max {
+        let b = a | qubit(a);
+        if b == a {return (a, i)}
+        a = b;
+    }
+    (a, max)
+}
+
+/// Path semantical continuous map `a ~> b`.
+#[cfg(feature = "quality")]
+pub fn cont(a: u64, b: u64) -> u64 {and(imply(a, b), imply(qubit(a), qubit(b)))}
+
+/// Path semantical quality `a ~~ b`.
+///
+/// Qualities in different namespaces are independent (see `with_qubit_namespace`).
+#[cfg(feature = "quality")]
+pub fn qual(a: u64, b: u64) -> u64 {and!(eq(a, b), qubit(a), qubit(b))}
+
+/// Path semantical aquality `a ~¬~ b`.
+///
+/// This is the quality of the negated propositions, `qual(not(a), not(b))`.
+/// Quality and aquality exclude each other.
+/// The alias is `aq`.
+///
+/// For more information, see [paper](https://github.com/advancedresearch/path_semantics/blob/master/papers-wip2/path-semantical-aquality.pdf).
+#[cfg(feature = "quality")]
+pub fn aqual(a: u64, b: u64) -> u64 {and!(eq(a, b), qubit(not(a)), qubit(not(b)))}
+
+/// Path semantical contravariant quality `a ¬~~ b`.
+///
+/// This is `eq(a, b)` where the qubit of `b` is `true` and the qubit of `a` is `false`.
+/// Unlike `qual` and `aqual`, it is not symmetric: `cq(a, b)` and `cq(b, a)` exclude each other.
+/// Together, `q(a, b)`, `aq(a, b)`, `cq(a, b)` and `cq(b, a)` cover `eq(a, b)` (Eq-Catuskoti).
+/// The alias is `cq`.
+///
+/// For more information, see [paper](https://github.com/advancedresearch/path_semantics/blob/master/papers-wip2/path-semantical-contravariant-quality.pdf).
+#[cfg(feature = "quality")]
+pub fn contra_qual(a: u64, b: u64) -> u64 {
+    and!(eq(a, b), not(qubit(a)), qubit(b))
+}
+
+/// Path semantical function `f_n(a, b)` up to some homotopy level `n`.
+///
+/// Each level prepares one qubit per argument, so the cost is linear in `n`.
+#[cfg(feature = "quality")]
+pub fn hom_f(f: fn(u64, u64) -> u64, n: u32, mut a: u64, mut b: u64) -> u64 {
+    let mut res = T;
+    for i in 0..n {
+        res = and(res, f(a, b));
+        // Higher levels can not make the result true again.
+        if res == F || i + 1 == n {break}
+        // Qubits are the same for the same proposition within a round.
+        let same = a == b;
+        a = qubit(a);
+        b = if same {a} else {qubit(b)};
+    }
+    res
+}
+
+/// Aligns implication of qubits up to some homotopy level.
+///
+/// This is also path semantical continuous map `a ~>_n b` up to homotopy level `n`.
+#[cfg(feature = "quality")]
+pub fn hom_imply(n: u32, a: u64, b: u64) -> u64 {
+    hom_f(imply, n, a, b)
+}
+
+/// Aligns equality of qubits up to some homotopy level.
+#[cfg(feature = "quality")]
+pub fn hom_eq(n: u32, a: u64, b: u64) -> u64 {
+    hom_f(eq, n, a, b)
+}
+
+/// Aligns logical AND of qubits up to some homotopy level.
+#[cfg(feature = "quality")]
+pub fn hom_and(n: u32, a: u64, b: u64) -> u64 {
+    hom_f(and, n, a, b)
+}
+
+/// Aligns logical OR of qubits up to some homotopy level.
+#[cfg(feature = "quality")]
+pub fn hom_or(n: u32, a: u64, b: u64) -> u64 {
+    hom_f(or, n, a, b)
+}
+
+/// Aligns logical XOR of qubits up to some homotopy level.
+#[cfg(feature = "quality")]
+pub fn hom_xor(n: u32, a: u64, b: u64) -> u64 {
+    hom_f(xor, n, a, b)
 }
 
 /// Assumes the path semantical core axiom.
+#[cfg(feature = "quality")]
 pub fn ps_core(a: u64, b: u64, c: u64, d: u64) -> u64 {
     imply(and!(qual(a, b), imply(a, c), imply(b, d)), qual(c, d))
 }
@@ -733,11 +1399,13 @@ pub fn ps_core(a: u64, b: u64, c: u64, d: u64) -> u64 {
 /// but using aquality instead of quality.
 ///
 /// For more information, see the "aqual" function.
+#[cfg(feature = "quality")]
 pub fn ps_acore(a: u64, b: u64, c: u64, d: u64) -> u64 {
     imply(and!(aqual(a, b), imply(a, c), imply(b, d)), aqual(c, d))
 }
 
 /// Assumes a strong version of the path semantical core axiom.
+#[cfg(feature = "quality")]
 pub fn ps_core_eq(a: u64, b: u64, c: u64, d: u64) -> u64 {
     imply(and!(imply(a, c), imply(b, d)), eq(qual(a, b), qual(c, d)))
 }
@@ -748,6 +1416,7 @@ pub fn ps_core_eq(a: u64, b: u64, c: u64, d: u64) -> u64 {
 /// but using aquality instead of quality.
 ///
 /// For more information, see the "aqual" function.
+#[cfg(feature = "quality")]
 pub fn ps_acore_eq(a: u64, b: u64, c: u64, d: u64) -> u64 {
     imply(and!(imply(a, c), imply(b, d)), eq(aqual(a, b), aqual(c, d)))
 }
@@ -755,6 +1424,7 @@ pub fn ps_acore_eq(a: u64, b: u64, c: u64, d: u64) -> u64 {
 /// Assumes the symmetric path semantical core axiom.
 ///
 /// This is the same as assuming both the core axiom and the acore axiom.
+#[cfg(feature = "quality")]
 pub fn ps_sym_core(a: u64, b: u64, c: u64, d: u64) -> u64 {
     and(ps_core(a, b, c, d), ps_acore(a, b, c, d))
 }
@@ -762,6 +1432,7 @@ pub fn ps_sym_core(a: u64, b: u64, c: u64, d: u64) -> u64 {
 /// Assumes a strong version of the symmetric path semantical core axiom.
 ///
 /// This is the same as assuming strong versions of both the core axiom and the acore axiom.
+#[cfg(feature = "quality")]
 pub fn ps_sym_core_eq(a: u64, b: u64, c: u64, d: u64) -> u64 {
     and(ps_core_eq(a, b, c, d), ps_acore_eq(a, b, c, d))
 }
@@ -775,6 +1446,7 @@ pub fn is_prop(x: u64, a: u64, b: u64) -> u64 {
 }
 
 /// Defines a set relation from a set `x` to potential members `a` and `b`.
+#[cfg(feature = "quality")]
 pub fn is_set(x: u64, a: u64, b: u64) -> u64 {
     imply(
         and(imply(a, x), imply(b, x)),
@@ -783,6 +1455,7 @@ pub fn is_set(x: u64, a: u64, b: u64) -> u64 {
 }
 
 /// Defines a groupoid relation from `x` to `a` and `b`.
+#[cfg(feature = "quality")]
 pub fn is_groupoid(x: u64, a: u64, b: u64) -> u64 {
     imply(
         and(imply(a, x), imply(b, x)),
@@ -791,6 +1464,7 @@ pub fn is_groupoid(x: u64, a: u64, b: u64) -> u64 {
 }
 
 /// Defines an n-groupoid relation from `x` to `a` and `b`.
+#[cfg(feature = "quality")]
 pub fn is_groupoid_n(n: u32, x: u64, a: u64, b: u64) -> u64 {
     let cond = and(imply(a, x), imply(b, x));
     let mut a = a;
@@ -803,6 +1477,7 @@ pub fn is_groupoid_n(n: u32, x: u64, a: u64, b: u64) -> u64 {
 }
 
 /// Defines a homotopy level `n` relation from `x` to `a` and `b`.
+#[cfg(feature = "quality")]
 pub fn is_hom_lev_n(n: u32, x: u64, a: u64, b: u64) -> u64 {
     match n {
         0 => T,
@@ -812,25 +1487,251 @@ pub fn is_hom_lev_n(n: u32, x: u64, a: u64, b: u64) -> u64 {
 }
 
 /// Assumes univalence axiom for some homotopy level.
+#[cfg(feature = "quality")]
 pub fn univ(n: u32, a: u64, b: u64) -> u64 {
     hom_eq(n, eq(a, b), hom_eq(n, a, b))
 }
 
+/// Formats a result after a label, used by `report!` and `report_measure!`.
+pub fn report_line(label: &str, res: bool) -> String {
+    format!("{}: {}", label, res)
+}
+
 /// Measures result repeatedly.
-pub fn measure<O: Observable>(n: u32, mut fun: impl FnMut() -> O) -> O {
-    let mut b = O::max_energy();
+pub fn measure<O: Observable>(n: u32, fun: impl FnMut() -> O) -> O {
+    measure_fold(n, O::max_energy(), O::min_energy, fun)
+}
+
+/// Measures result repeatedly, combining results with a user-defined reduction.
+///
+/// `measure` is the same as using `max_energy` as initial value and `min_energy` as reduction.
+pub fn measure_fold<O, R: FnMut(O, O) -> O>(
+    n: u32,
+    init: O,
+    mut reduce: R,
+    mut fun: impl FnMut() -> O
+) -> O {
+    let mut b = init;
     for _ in 0..n {
-        b = b.min_energy(fun());
+        b = reduce(b, fun());
     }
     b
 }
 
-fn call(mut fun: impl FnMut() -> u64) -> u64 {
-    let mut r = rand::random::<u64>();
-    let guard = current::CurrentGuard::new(&mut r);
-    let res = fun();
-    drop(guard);
-    res
+/// Measures how close an n-argument statement got to failing over random rounds.
+///
+/// Returns the largest number of failing cases `2^n - count` observed in a round,
+/// which is `0` if the statement was a tautology every round.
+/// A barely-true statement fails for few cases, while a robust one never fails.
+pub fn measure_margin(rounds: u32, n: usize, fun: &mut dyn FnMut(&[u64]) -> u64) -> u64 {
+    (1 << n) - measure(rounds, || countn(n, fun))
+}
+
+/// Measures result repeatedly, returning the fraction of times it was `true`.
+///
+/// This estimates how often a statement holds, e.g. a statement using qubits that is not a tautology.
+/// Returns `NaN` when `n` is `0`.
+pub fn measure_avg(n: u32, mut fun: impl FnMut() -> bool) -> f64 {
+    (0..n).filter(|_| fun()).count() as f64 / n as f64
+}
+
+/// Measures how qualitatively equal two n-argument propositions are over random rounds.
+///
+/// Returns a score in `[0, 1]`, the fraction of rounds where `a ~~ b` agrees
+/// with both `a ~~ a` and `b ~~ b` for all cases.
+///
+/// Quality is not compared to `T` since `a ~~ a` is `~a`, which is rarely true in all cases.
+/// Identical propositions score `1.0`, while unrelated propositions score lower.
+#[cfg(feature = "quality")]
+pub fn quality_score(a: &PredN, b: &PredN, n: usize, rounds: u32) -> f64 {
+    let hits = (0..rounds).filter(|_| proven(n, &mut |vs| {
+        let (a, b) = (a(vs), b(vs));
+        let q = qual(a, b);
+        and(eq(q, qual(a, a)), eq(q, qual(b, b)))
+    })).count();
+    hits as f64 / rounds as f64
+}
+
+/// Computes the Wald confidence interval of a success frequency.
+///
+/// Returns the estimated frequency and the half-width of the interval,
+/// such that the true frequency is within `estimate ± half-width` at the given confidence.
+///
+/// The Wald interval is degenerate for frequencies near `0` or `1` with few rounds,
+/// where the half-width becomes zero.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn frequency_ci(successes: u32, rounds: u32, confidence: f64) -> (f64, f64) {
+    let p = successes as f64 / rounds as f64;
+    (p, z_score(confidence) * (p * (1.0 - p) / rounds as f64).sqrt())
+}
+
+/// Gets the two-sided z-score of the standard normal distribution for a confidence level.
+///
+/// Uses the rational approximation by Abramowitz and Stegun (26.2.23).
+#[cfg(feature = "std")]
+fn z_score(confidence: f64) -> f64 {
+    let t = (-2.0 * ((1.0 - confidence) / 2.0).ln()).sqrt();
+    t - (2.515517 + 0.802853 * t + 0.010328 * t * t) /
+        (1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t)
+}
+
+/// Measures the success frequency of a statement until it is known within a margin.
+///
+/// Keeps sampling until the Wald interval (see `frequency_ci`)
+/// is within `margin` at the given confidence.
+/// Returns the estimated frequency and the number of rounds used,
+/// or `None` when the margin is not reached within `max_rounds`.
+///
+/// Since the Wald interval is degenerate for few rounds,
+/// at least 30 rounds are used.
+///
+/// Panics unless `0 < confidence < 1` and `margin > 0`.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn measure_to_confidence(
+    mut fun: impl FnMut() -> bool,
+    confidence: f64,
+    margin: f64,
+    max_rounds: u32
+) -> Option<(f64, u32)> {
+    assert!(confidence > 0.0 && confidence < 1.0, "Expected confidence in `(0, 1)`, found {}", confidence);
+    assert!(margin > 0.0, "Expected margin greater than `0`, found {}", margin);
+    let mut successes = 0;
+    for rounds in 1..=max_rounds {
+        if fun() {successes += 1}
+        if rounds >= 30 {
+            let (p, w) = frequency_ci(successes, rounds, confidence);
+            if w <= margin {return Some((p, rounds))}
+        }
+    }
+    None
+}
+
+/// Runs a closure where every round uses a fixed seed instead of a random one.
+///
+/// This reproduces the qubits of a round, e.g. to debug a counterexample
+/// found by `measure_counterexample`.
+#[cfg(feature = "quality")]
+pub fn with_round_seed<R>(seed: u64, fun: impl FnOnce() -> R) -> R {
+    with_local(&ROUND_SEED, Some(seed), fun)
+}
+
+/// Measures an n-argument statement over random rounds, capturing the first counterexample.
+///
+/// Returns the seed of the first round where the statement failed,
+/// together with the first failing assignment of the arguments,
+/// or `None` if the statement held in all rounds.
+/// The round is reproduced by proving the statement inside `with_round_seed`.
+#[cfg(feature = "quality")]
+pub fn measure_counterexample(
+    rounds: u32,
+    n: usize,
+    fun: &mut dyn FnMut(&[u64]) -> u64
+) -> Option<(u64, Vec<bool>)> {
+    (0..rounds).find_map(|_| {
+        let seed = rand::random::<u64>();
+        with_round_seed(seed, || solutions(n, |vs| not(fun(vs))).next()).map(|xs| (seed, xs))
+    })
+}
+
+/// Measures result repeatedly using deterministic rounds, same as `measure`.
+///
+/// Measurement `i` uses the round seed `seed + i` (see `with_round_seed`),
+/// such that the result is the same every time, e.g. in regression tests.
+#[cfg(feature = "quality")]
+pub fn measure_seeded<O: Observable>(seed: u64, n: u32, fun: impl FnMut() -> O) -> O {
+    measure(n, seeded_rounds(seed, fun))
+}
+
+/// Runs a statement using the round seed `seed + i` the `i`-th time it is called.
+#[cfg(feature = "quality")]
+fn seeded_rounds<O>(seed: u64, mut fun: impl FnMut() -> O) -> impl FnMut() -> O {
+    let mut i = 0;
+    move || {
+        let res = with_round_seed(seed.wrapping_add(i), &mut fun);
+        i += 1;
+        res
+    }
+}
+
+/// Measures result repeatedly, passing the context of the round to every measurement.
+///
+/// Every measurement uses a new random round, or the fixed seed inside `with_round_seed`
+/// (e.g. using `measure_seeded` or `Solver::with_seed`).
+/// The round is also used while counting inside the measurement,
+/// such that `ctx.qubit(a)` is the same as `qubit(a)`.
+/// The context can be passed to other threads, which do not share the current round.
+#[cfg(feature = "quality")]
+pub fn measure_ctx<O: Observable>(n: u32, mut fun: impl FnMut(&QubitCtx) -> O) -> O {
+    measure(n, || {
+        let ctx = QubitCtx::scoped(round_seed());
+        with_round_seed(ctx.round, || fun(&ctx))
+    })
+}
+
+/// Configures repeated measurements of statements using qubits.
+///
+/// This replaces calling `measure` or `measure_seeded` with the same settings,
+/// e.g. `Solver::default().with_seed(42).prove(|| prove!(...))`.
+#[cfg(feature = "quality")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Solver {
+    /// The number of measurements.
+    pub runs: u32,
+    /// The seed of the first round, or `None` for random rounds (see `measure_seeded`).
+    pub seed: Option<u64>,
+}
+
+#[cfg(feature = "quality")]
+impl Default for Solver {
+    fn default() -> Solver {Solver {runs: 100, seed: None}}
+}
+
+#[cfg(feature = "quality")]
+impl Solver {
+    /// Sets the number of measurements.
+    pub fn with_runs(self, runs: u32) -> Solver {Solver {runs, ..self}}
+
+    /// Sets the seed of the first round, such that results are the same every time.
+    pub fn with_seed(self, seed: u64) -> Solver {Solver {seed: Some(seed), ..self}}
+
+    /// Returns `true` if the statement is true in all rounds, same as `measure`.
+    pub fn prove<F: FnMut() -> bool>(&self, f: F) -> bool {
+        match self.seed {
+            Some(seed) => measure_seeded(seed, self.runs, f),
+            None => measure(self.runs, f),
+        }
+    }
+
+    /// Returns the fraction of rounds where the statement is true, same as `measure_avg`.
+    pub fn prob<F: FnMut() -> bool>(&self, f: F) -> f64 {
+        match self.seed {
+            Some(seed) => measure_avg(self.runs, seeded_rounds(seed, f)),
+            None => measure_avg(self.runs, f),
+        }
+    }
+}
+
+/// Gets the fixed seed of rounds (see `with_round_seed`), or a random seed.
+#[cfg(feature = "quality")]
+fn round_seed() -> u64 {
+    ROUND_SEED.with(|x| x.get()).unwrap_or_else(rand::random::<u64>)
+}
+
+#[cfg(feature = "quality")]
+fn call(fun: impl FnMut() -> u64) -> u64 {call_seeded(round_seed(), fun)}
+
+/// Runs a round without qubits.
+#[cfg(not(feature = "quality"))]
+fn call(mut fun: impl FnMut() -> u64) -> u64 {fun()}
+
+/// Runs a round using a seed, creating the context of the round.
+#[cfg(feature = "quality")]
+fn call_seeded(r: u64, fun: impl FnMut() -> u64) -> u64 {
+    with_local(&ROUND, Some(QubitCtx::scoped(r)), fun)
 }
 
 /// Counts the number of solutions of a 1-argument boolean function.
@@ -899,20 +1800,117 @@ pub fn count10<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64
     call(|| (f)(P0, P1, P2, P3, P4, P5, T, T, T, F)).count_ones() as u64 +
     call(|| (f)(P0, P1, P2, P3, P4, P5, T, T, T, T)).count_ones() as u64
 }
+/// Counts the number of solutions of a 11-argument boolean function.
+pub fn count11<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> u64 {
+    count10(&mut |a, b, c, d, e, g, h, i, j, k| (f)(a, b, c, d, e, g, h, i, j, k, F)) +
+    count10(&mut |a, b, c, d, e, g, h, i, j, k| (f)(a, b, c, d, e, g, h, i, j, k, T))
+}
+/// Counts the number of solutions of a 12-argument boolean function.
+pub fn count12<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> u64 {
+    count11(&mut |a, b, c, d, e, g, h, i, j, k, l| (f)(a, b, c, d, e, g, h, i, j, k, l, F)) +
+    count11(&mut |a, b, c, d, e, g, h, i, j, k, l| (f)(a, b, c, d, e, g, h, i, j, k, l, T))
+}
+/// Counts the number of solutions of a 13-argument boolean function.
+pub fn count13<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> u64 {
+    count12(&mut |a, b, c, d, e, g, h, i, j, k, l, m| (f)(a, b, c, d, e, g, h, i, j, k, l, m, F)) +
+    count12(&mut |a, b, c, d, e, g, h, i, j, k, l, m| (f)(a, b, c, d, e, g, h, i, j, k, l, m, T))
+}
+/// Counts the number of solutions of a 14-argument boolean function.
+pub fn count14<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> u64 {
+    count13(&mut |a, b, c, d, e, g, h, i, j, k, l, m, n| (f)(a, b, c, d, e, g, h, i, j, k, l, m, n, F)) +
+    count13(&mut |a, b, c, d, e, g, h, i, j, k, l, m, n| (f)(a, b, c, d, e, g, h, i, j, k, l, m, n, T))
+}
+/// Counts the number of solutions of a 15-argument boolean function.
+pub fn count15<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> u64 {
+    count14(&mut |a, b, c, d, e, g, h, i, j, k, l, m, n, o| (f)(a, b, c, d, e, g, h, i, j, k, l, m, n, o, F)) +
+    count14(&mut |a, b, c, d, e, g, h, i, j, k, l, m, n, o| (f)(a, b, c, d, e, g, h, i, j, k, l, m, n, o, T))
+}
+/// Counts the number of solutions of a 16-argument boolean function.
+pub fn count16<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> u64 {
+    count15(&mut |a, b, c, d, e, g, h, i, j, k, l, m, n, o, p| (f)(a, b, c, d, e, g, h, i, j, k, l, m, n, o, p, F)) +
+    count15(&mut |a, b, c, d, e, g, h, i, j, k, l, m, n, o, p| (f)(a, b, c, d, e, g, h, i, j, k, l, m, n, o, p, T))
+}
+/// Counts the number of solutions of a 7-argument boolean function without qubits.
+///
+/// Same as `count7`, but skips preparing a random round for every call.
+/// Panics if the function uses qubits.
+pub fn count7_classical<F: FnMut(u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> u64 {
+    (f)(P0, P1, P2, P3, P4, P5, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T).count_ones() as u64
+}
+/// Counts the number of solutions of an 8-argument boolean function without qubits.
+///
+/// Same as `count8`, but skips preparing a random round for every call.
+/// Panics if the function uses qubits.
+pub fn count8_classical<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> u64 {
+    (f)(P0, P1, P2, P3, P4, P5, F, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, F, T).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, T).count_ones() as u64
+}
+/// Counts the number of solutions of a 9-argument boolean function without qubits.
+///
+/// Same as `count9`, but skips preparing a random round for every call.
+/// Panics if the function uses qubits.
+pub fn count9_classical<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> u64 {
+    (f)(P0, P1, P2, P3, P4, P5, F, F, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, F, F, T).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, F, T, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, F, T, T).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, F, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, F, T).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, T, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, T, T).count_ones() as u64
+}
+/// Counts the number of solutions of a 10-argument boolean function without qubits.
+///
+/// Same as `count10`, but skips preparing a random round for every call.
+/// Panics if the function uses qubits.
+pub fn count10_classical<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> u64 {
+    (f)(P0, P1, P2, P3, P4, P5, F, F, F, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, F, F, F, T).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, F, F, T, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, F, F, T, T).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, F, T, F, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, F, T, F, T).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, F, T, T, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, F, T, T, T).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, F, F, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, F, F, T).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, F, T, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, F, T, T).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, T, F, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, T, F, T).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, T, T, F).count_ones() as u64 +
+    (f)(P0, P1, P2, P3, P4, P5, T, T, T, T).count_ones() as u64
+}
 /// Counts the number of solutions of an n-argument boolean function.
 pub fn countn(n: usize, fun: &mut dyn FnMut(&[u64]) -> u64) -> u64 {
     match n {
-        0 => call(|| fun(&[])).count_ones() as u64,
+        0 => call(|| fun(&[])) & 1,
         1 => count1(&mut |a| fun(&[a])) as u64,
         2 => count2(&mut |a, b| fun(&[a, b])) as u64,
         3 => count3(&mut |a, b, c| fun(&[a, b, c])) as u64,
         4 => count4(&mut |a, b, c, d| fun(&[a, b, c, d])) as u64,
         5 => count5(&mut |a, b, c, d, e| fun(&[a, b, c, d, e])) as u64,
         6 => count6(&mut |a, b, c, d, e, f| fun(&[a, b, c, d, e, f])) as u64,
+        #[cfg(feature = "quality")]
         7 => count7(&mut |a, b, c, d, e, f, g| fun(&[a, b, c, d, e, f, g])) as u64,
+        #[cfg(feature = "quality")]
         8 => count8(&mut |a, b, c, d, e, f, g, h| fun(&[a, b, c, d, e, f, g, h])) as u64,
+        #[cfg(feature = "quality")]
         9 => count9(&mut |a, b, c, d, e, f, g, h, i| fun(&[a, b, c, d, e, f, g, h, i])) as u64,
+        #[cfg(feature = "quality")]
         10 => count10(&mut |a, b, c, d, e, f, g, h, i, j| fun(&[a, b, c, d, e, f, g, h, i, j])) as u64,
+        // Without qubits there are no rounds to prepare.
+        #[cfg(not(feature = "quality"))]
+        7 => count7_classical(&mut |a, b, c, d, e, f, g| fun(&[a, b, c, d, e, f, g])),
+        #[cfg(not(feature = "quality"))]
+        8 => count8_classical(&mut |a, b, c, d, e, f, g, h| fun(&[a, b, c, d, e, f, g, h])),
+        #[cfg(not(feature = "quality"))]
+        9 => count9_classical(&mut |a, b, c, d, e, f, g, h, i| fun(&[a, b, c, d, e, f, g, h, i])),
+        #[cfg(not(feature = "quality"))]
+        10 => count10_classical(&mut |a, b, c, d, e, f, g, h, i, j| fun(&[a, b, c, d, e, f, g, h, i, j])),
         _ => {
             if n >= 19 {
                 let ref mut args = vec![0; n];
@@ -953,6 +1951,293 @@ pub fn countn(n: usize, fun: &mut dyn FnMut(&[u64]) -> u64) -> u64 {
     }
 }
 
+/// An error when counting solutions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveError {
+    /// A qubit was prepared outside a round.
+    NoRound,
+    /// The number of solutions does not fit in `u64`.
+    TooManyArguments(usize),
+    /// The function panicked, e.g. by reading an argument out of range.
+    Panic(String),
+}
+
+impl core::fmt::Display for SolveError {
+    fn fmt(&self, w: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            SolveError::NoRound => write!(w, "No current round, qubits must be prepared while counting"),
+            SolveError::TooManyArguments(n) => write!(w, "Too many arguments to count: {}", n),
+            SolveError::Panic(msg) => write!(w, "Function panicked: {}", msg),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SolveError {}
+
+/// Counts the number of solutions of an n-argument boolean function, same as `countn`.
+///
+/// Returns an error instead of panicking when the function panics,
+/// e.g. when reading more arguments than `n`.
+/// The number of arguments is checked before counting, without calling the function.
+///
+/// Panics are caught using `std::panic::catch_unwind`, which has some limitations:
+///
+/// - With `panic = "abort"`, the process aborts instead of returning an error
+/// - The panic hook still runs, which by default prints the panic to stderr
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn try_countn(n: usize, fun: &mut dyn FnMut(&[u64]) -> u64) -> Result<u64, SolveError> {
+    // The number of solutions is at most `2^n`.
+    if n >= 64 {return Err(SolveError::TooManyArguments(n))}
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| countn(n, fun))).map_err(|err| {
+        SolveError::Panic(err.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| err.downcast_ref::<String>().cloned())
+            .unwrap_or_default())
+    })
+}
+
+/// Counts the solutions of an n-argument boolean function where the first arguments are fixed.
+///
+/// Only the free arguments after the prefix are enumerated.
+/// Sub-cube counts can be memoized by the prefix and reused across related queries,
+/// since summing the counts for both values of the next argument gives the count of the shorter prefix.
+pub fn count_cube(n: usize, fixed_prefix: &[bool], fun: &mut dyn FnMut(&[u64]) -> u64) -> u64 {
+    let k = fixed_prefix.len();
+    assert!(k <= n, "Prefix is longer than the number of arguments");
+    let mut args: Vec<u64> = fixed_prefix.iter().map(|&x| if x {T} else {F}).collect();
+    args.resize(n, F);
+    countn(n - k, &mut |vs: &[u64]| {
+        args[k..].copy_from_slice(vs);
+        fun(&args)
+    })
+}
+
+/// Counts the number of solutions of an n-argument boolean function in parallel.
+///
+/// Splits the cases on the first arguments like `countn` does for high arity,
+/// counting every part on a separate task (see `count_cube`).
+/// The function must be `Fn + Sync` since it is shared between threads.
+///
+/// Requires the `parallel` feature.
+#[cfg(feature = "parallel")]
+pub fn countn_par(n: usize, fun: &(dyn Fn(&[u64]) -> u64 + Sync)) -> u64 {
+    use rayon::prelude::*;
+
+    if n <= 10 {return countn(n, &mut |vs| fun(vs))}
+    let k = if n >= 19 {9} else {5};
+    (0..1u64 << k).into_par_iter().map(|i| {
+        let prefix: Vec<bool> = (0..k).map(|j| (i >> j) & 1 == 1).collect();
+        count_cube(n, &prefix, &mut |vs| fun(vs))
+    }).sum()
+}
+
+/// Returns `true` if proposition is correct, `false` otherwise, counting in parallel.
+///
+/// Requires the `parallel` feature (see `countn_par`).
+#[cfg(feature = "parallel")]
+pub fn proven_par(n: usize, fun: &(dyn Fn(&[u64]) -> u64 + Sync)) -> bool {
+    countn_par(n, fun) == 1 << n
+}
+
+/// Gets the number of chunks of 64 cases for an n-argument boolean function.
+fn chunks(n: usize) -> u64 {if n > 6 {1 << (n - 6)} else {1}}
+
+/// Evaluates an n-argument boolean function for one chunk of cases.
+///
+/// The first 6 arguments use the bit patterns `P0..P5`,
+/// while the rest alternate `F` and `T` by the bits of `chunk`.
+/// Case `chunk << 6 | bit` assigns argument `i` to bit `i` of the case.
+/// Bits outside the cases are masked out.
+fn eval_chunk(
+    n: usize,
+    chunk: u64,
+    args: &mut [u64],
+    fun: &mut dyn FnMut(&[u64]) -> u64
+) -> u64 {
+    const PS: [u64; 6] = [P0, P1, P2, P3, P4, P5];
+    for (i, arg) in args.iter_mut().enumerate().take(n) {
+        *arg = if i < 6 {PS[i]} else if (chunk >> (i - 6)) & 1 == 1 {T} else {F};
+    }
+    let mask = if n >= 6 {T} else {(1 << (1 << n)) - 1};
+    call(|| fun(&args[..n])) & mask
+}
+
+/// An iterator over the solutions of an n-argument boolean function.
+///
+/// Created by `solutions`.
+pub struct Solutions<F> {
+    n: usize,
+    fun: F,
+    chunk: u64,
+    bits: u64,
+    args: Vec<u64>,
+}
+
+/// Lazily enumerates the solutions of an n-argument boolean function.
+///
+/// Each solution is the assignment of the arguments.
+/// Solutions are evaluated 64 cases at a time, such that memory stays constant.
+pub fn solutions<F: FnMut(&[u64]) -> u64>(n: usize, fun: F) -> Solutions<F> {
+    Solutions {n, fun, chunk: 0, bits: 0, args: vec![0; n]}
+}
+
+impl<F: FnMut(&[u64]) -> u64> Iterator for Solutions<F> {
+    type Item = Vec<bool>;
+
+    fn next(&mut self) -> Option<Vec<bool>> {
+        loop {
+            if self.bits != 0 {
+                let case = (self.chunk - 1) << 6 | self.bits.trailing_zeros() as u64;
+                self.bits &= self.bits - 1;
+                return Some((0..self.n).map(|i| (case >> i) & 1 == 1).collect());
+            }
+            if self.chunk >= chunks(self.n) {return None}
+            self.bits = eval_chunk(self.n, self.chunk, &mut self.args, &mut self.fun);
+            self.chunk += 1;
+        }
+    }
+}
+
+/// Finds the first solution of an n-argument boolean function, if any.
+///
+/// The first solution is the lowest case where the function is true (see `solutions`).
+pub fn find_model(n: usize, f: &mut dyn FnMut(&[u64]) -> u64) -> Option<Vec<bool>> {
+    solutions(n, f).next()
+}
+
+/// Finds all solutions of an n-argument boolean function, ordered by case.
+///
+/// For many solutions, use `models` to avoid keeping all solutions in memory.
+pub fn find_all_models(n: usize, f: &mut dyn FnMut(&[u64]) -> u64) -> Vec<Vec<bool>> {
+    solutions(n, f).collect()
+}
+
+/// Lazily enumerates all solutions of an n-argument boolean function, ordered by case.
+///
+/// Same as `find_all_models`, but yields one solution at a time (see `solutions`).
+pub fn models<'a>(n: usize, f: &'a mut dyn FnMut(&[u64]) -> u64) -> impl Iterator<Item = Vec<bool>> + 'a {
+    solutions(n, f)
+}
+
+/// Computes the probability of an n-argument boolean function,
+/// where argument `i` is `true` with probability `weights[i]` independently of the others.
+///
+/// Each solution is weighted by the product of `weights[i]` for true arguments
+/// and `1 - weights[i]` for false arguments.
+/// With all weights `0.5`, this is `countn(n, f)` divided by `2^n`.
+pub fn weighted_count(n: usize, weights: &[f64], f: &mut dyn FnMut(&[u64]) -> u64) -> f64 {
+    assert_eq!(weights.len(), n, "Expected one weight per argument");
+    let weight = |i: usize, v: bool| if v {weights[i]} else {1.0 - weights[i]};
+    // The weights of the cases within a chunk, by the first 6 arguments.
+    let low: Vec<f64> = (0..64_u64).map(|bit| {
+        (0..n.min(6)).map(|i| weight(i, (bit >> i) & 1 == 1)).product()
+    }).collect();
+    let mut args = vec![0; n];
+    (0..chunks(n)).map(|chunk| {
+        let high: f64 = (6..n).map(|i| weight(i, (chunk >> (i - 6)) & 1 == 1)).product();
+        let mut bits = eval_chunk(n, chunk, &mut args, f);
+        let mut sum = 0.0;
+        while bits != 0 {
+            sum += low[bits.trailing_zeros() as usize];
+            bits &= bits - 1;
+        }
+        sum * high
+    }).sum()
+}
+
+/// Samples `k` solutions uniformly at random from an n-argument boolean function.
+///
+/// Uses reservoir sampling over `solutions`, such that memory stays `O(k)`.
+/// Returns all solutions when there are at most `k`.
+///
+/// Requires the `quality` feature.
+#[cfg(feature = "quality")]
+pub fn sample_solutions<F, R>(n: usize, fun: F, k: usize, rng: &mut R) -> Vec<Vec<bool>>
+    where F: FnMut(&[u64]) -> u64, R: rand::Rng
+{
+    let mut res = Vec::with_capacity(k);
+    for (i, solution) in solutions(n, fun).enumerate() {
+        if i < k {res.push(solution)}
+        else {
+            let j = rng.gen_range(0..=i);
+            if j < k {res[j] = solution}
+        }
+    }
+    res
+}
+
+/// The truth tables of two n-argument boolean functions side by side.
+///
+/// Created by `table_diff`.
+/// Displays one row per case, marking the rows where the functions differ with `*`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableDiff {
+    /// The assignment of the arguments and the results of both functions, ordered by case.
+    pub rows: Vec<(Vec<bool>, bool, bool)>,
+}
+
+impl TableDiff {
+    /// Gets the rows where the functions differ.
+    pub fn differing(&self) -> impl Iterator<Item = &(Vec<bool>, bool, bool)> {
+        self.rows.iter().filter(|(_, a, b)| a != b)
+    }
+}
+
+impl core::fmt::Display for TableDiff {
+    fn fmt(&self, w: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let bit = |x: bool| if x {'1'} else {'0'};
+        for (xs, a, b) in &self.rows {
+            for &x in xs {write!(w, "{} ", bit(x))?}
+            write!(w, "| {} {}", bit(*a), bit(*b))?;
+            if a != b {write!(w, " *")?}
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the truth tables of two n-argument boolean functions row by row.
+///
+/// Returns the results of both functions for every assignment of the arguments (see `TableDiff`).
+/// This is used to show why two functions are different.
+pub fn table_diff(n: usize, f: impl Fn(&[u64]) -> u64, g: impl Fn(&[u64]) -> u64) -> TableDiff {
+    let mut args = vec![0; n];
+    let mut rows = Vec::with_capacity(1 << n);
+    for chunk in 0..chunks(n) {
+        let a = eval_chunk(n, chunk, &mut args, &mut |vs| f(vs));
+        let b = eval_chunk(n, chunk, &mut args, &mut |vs| g(vs));
+        for bit in 0..(1u64 << n).min(64) {
+            let case = chunk << 6 | bit;
+            rows.push(((0..n).map(|i| (case >> i) & 1 == 1).collect(), (a >> bit) & 1 == 1, (b >> bit) & 1 == 1));
+        }
+    }
+    TableDiff {rows}
+}
+
+/// The function filtering worlds in `Models`.
+type ModelsFn<'a> = Box<dyn FnMut(&[u64]) -> u64 + 'a>;
+
+/// An iterator over the worlds of a logical system.
+///
+/// Created by `Prove::models_iter`.
+pub struct Models<'a, S> {
+    solutions: Solutions<ModelsFn<'a>>,
+    _s: core::marker::PhantomData<S>,
+}
+
+impl<'a, S: Construct> Iterator for Models<'a, S> {
+    type Item = S;
+
+    fn next(&mut self) -> Option<S> {
+        self.solutions.next().map(|xs| {
+            let vs: Vec<u64> = xs.into_iter().map(|x| if x {T} else {F}).collect();
+            Construct::construct(&vs)
+        })
+    }
+}
+
 /// Path Semantical Logic: Counts the number of solutions of a 1-argument boolean function,
 ///
 /// For more information, see the section "Path Semantical Logic" at the top level documentation.
@@ -1209,10 +2494,215 @@ pub fn prove10<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64
     count10(f) == 1024
 }
 /// Returns `true` if proposition is correct, `false` otherwise.
-pub fn proven<F: FnMut(&[u64]) -> u64>(n: usize, f: &mut F) -> bool {
-    countn(n, f) == 1 << n
+pub fn prove11<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> bool {
+    count11(f) == 2048
 }
-
+/// Returns `true` if proposition is correct, `false` otherwise.
+pub fn prove12<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> bool {
+    count12(f) == 4096
+}
+/// Returns `true` if proposition is correct, `false` otherwise.
+pub fn prove13<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> bool {
+    count13(f) == 8192
+}
+/// Returns `true` if proposition is correct, `false` otherwise.
+pub fn prove14<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> bool {
+    count14(f) == 16384
+}
+/// Returns `true` if proposition is correct, `false` otherwise.
+pub fn prove15<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> bool {
+    count15(f) == 32768
+}
+/// Returns `true` if proposition is correct, `false` otherwise.
+pub fn prove16<F: FnMut(u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> bool {
+    count16(f) == 65536
+}
+/// Returns `true` if proposition is correct, `false` otherwise.
+pub fn proven<F: FnMut(&[u64]) -> u64>(n: usize, f: &mut F) -> bool {
+    countn(n, f) == 1 << n
+}
+
+/// Returns `Ok(())` if proposition is correct, otherwise the first counterexample.
+///
+/// The counterexample is the assignment of the arguments in the first case where the proposition is false.
+/// Case `i` assigns argument `j` to bit `j` of `i` (see `solutions`).
+pub fn proven_ce<F: FnMut(&[u64]) -> u64>(n: usize, f: &mut F) -> Result<(), Vec<bool>> {
+    match solutions(n, |vs| not(f(vs))).next() {
+        None => Ok(()),
+        Some(xs) => Err(xs),
+    }
+}
+/// Returns `Ok(())` if proposition is correct, otherwise the first counterexample (see `proven_ce`).
+pub fn prove_ce1<F: FnMut(u64) -> u64>(f: &mut F) -> Result<(), Vec<bool>> {
+    proven_ce(1, &mut |vs| f(vs[0]))
+}
+/// Returns `Ok(())` if proposition is correct, otherwise the first counterexample (see `proven_ce`).
+pub fn prove_ce2<F: FnMut(u64, u64) -> u64>(f: &mut F) -> Result<(), Vec<bool>> {
+    proven_ce(2, &mut |vs| f(vs[0], vs[1]))
+}
+/// Returns `Ok(())` if proposition is correct, otherwise the first counterexample (see `proven_ce`).
+pub fn prove_ce3<F: FnMut(u64, u64, u64) -> u64>(f: &mut F) -> Result<(), Vec<bool>> {
+    proven_ce(3, &mut |vs| f(vs[0], vs[1], vs[2]))
+}
+/// Returns `Ok(())` if proposition is correct, otherwise the first counterexample (see `proven_ce`).
+pub fn prove_ce4<F: FnMut(u64, u64, u64, u64) -> u64>(f: &mut F) -> Result<(), Vec<bool>> {
+    proven_ce(4, &mut |vs| f(vs[0], vs[1], vs[2], vs[3]))
+}
+/// Returns `Ok(())` if proposition is correct, otherwise the first counterexample (see `proven_ce`).
+pub fn prove_ce5<F: FnMut(u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> Result<(), Vec<bool>> {
+    proven_ce(5, &mut |vs| f(vs[0], vs[1], vs[2], vs[3], vs[4]))
+}
+/// Returns `Ok(())` if proposition is correct, otherwise the first counterexample (see `proven_ce`).
+pub fn prove_ce6<F: FnMut(u64, u64, u64, u64, u64, u64) -> u64>(f: &mut F) -> Result<(), Vec<bool>> {
+    proven_ce(6, &mut |vs| f(vs[0], vs[1], vs[2], vs[3], vs[4], vs[5]))
+}
+
+/// A certificate of a proof that can be checked independently.
+///
+/// Stores the result column of the proposition for every case,
+/// with the claim that the number of true cases is `2^n`.
+///
+/// Converts to and from text, e.g. `2 4 f` for a tautology of 2 arguments,
+/// where the result column is written as hexadecimal words.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Certificate {
+    /// The number of arguments.
+    pub n: usize,
+    /// The claimed number of true cases.
+    pub count: u64,
+    /// The result column, 64 cases per word (see `solutions` for the layout).
+    pub table: Vec<u64>,
+}
+
+impl core::fmt::Display for Certificate {
+    fn fmt(&self, w: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(w, "{} {}", self.n, self.count)?;
+        for x in &self.table {write!(w, " {:x}", x)?}
+        Ok(())
+    }
+}
+
+impl core::str::FromStr for Certificate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Certificate, String> {
+        let mut words = s.split_whitespace();
+        let mut next = |name: &str| words.next().ok_or(format!("Expected {}", name));
+        let n = next("number of arguments")?.parse().map_err(|_| "Expected number of arguments")?;
+        let count = next("count")?.parse().map_err(|_| "Expected count")?;
+        let table = words.map(|x| u64::from_str_radix(x, 16).map_err(|_| format!("Invalid word `{}`", x)))
+            .collect::<Result<Vec<u64>, String>>()?;
+        Ok(Certificate {n, count, table})
+    }
+}
+
+/// Proves an n-argument proposition and returns a certificate of the proof.
+///
+/// Returns `None` if the proposition is not a tautology.
+pub fn prove_with_certificate(n: usize, fun: &mut dyn FnMut(&[u64]) -> u64) -> Option<Certificate> {
+    let mut args = vec![0; n];
+    let table: Vec<u64> = (0..chunks(n)).map(|chunk| eval_chunk(n, chunk, &mut args, fun)).collect();
+    let count = table.iter().map(|x| x.count_ones() as u64).sum();
+    if count == 1 << n {Some(Certificate {n, count, table})} else {None}
+}
+
+/// Checks a certificate without running the prover.
+///
+/// Returns `true` if the result column has the right size,
+/// is true for every case and agrees with the claimed count.
+pub fn verify_certificate(cert: &Certificate) -> bool {
+    let n = cert.n;
+    if n >= 64 || cert.table.len() as u64 != chunks(n) {return false}
+    let mask = if n >= 6 {T} else {(1 << (1 << n)) - 1};
+    cert.count == 1 << n &&
+    cert.table.iter().all(|&x| x == mask) &&
+    cert.table.iter().map(|x| x.count_ones() as u64).sum::<u64>() == cert.count
+}
+
+/// A named law of logic, reading the arguments by index.
+pub type Law = (&'static str, fn(&[u64]) -> u64);
+
+/// Named tautologies of classical logic with up to 3 arguments.
+///
+/// Used by `self_test`.
+pub static CLASSICAL_LAWS: &[Law] = &[
+    ("identity", |vs| imply(vs[0], vs[0])),
+    ("excluded middle", |vs| or(vs[0], not(vs[0]))),
+    ("non-contradiction", |vs| not(and(vs[0], not(vs[0])))),
+    ("double negation", |vs| eq(not(not(vs[0])), vs[0])),
+    ("modus ponens", |vs| imply(and(vs[0], imply(vs[0], vs[1])), vs[1])),
+    ("modus tollens", |vs| imply(and(imply(vs[0], vs[1]), not(vs[1])), not(vs[0]))),
+    ("contrapositive", |vs| eq(imply(vs[0], vs[1]), imply(not(vs[1]), not(vs[0])))),
+    ("hypothetical syllogism", |vs| imply(imply3(vs[0], vs[1], vs[2]), imply(vs[0], vs[2]))),
+    ("disjunctive syllogism", |vs| imply(and(or(vs[0], vs[1]), not(vs[0])), vs[1])),
+    ("De Morgan AND", |vs| eq(not(and(vs[0], vs[1])), or(not(vs[0]), not(vs[1])))),
+    ("De Morgan OR", |vs| eq(not(or(vs[0], vs[1])), and(not(vs[0]), not(vs[1])))),
+    ("AND distributes over OR", |vs| {
+        eq(and(vs[0], or(vs[1], vs[2])), or(and(vs[0], vs[1]), and(vs[0], vs[2])))
+    }),
+    ("OR distributes over AND", |vs| {
+        eq(or(vs[0], and(vs[1], vs[2])), and(or(vs[0], vs[1]), or(vs[0], vs[2])))
+    }),
+    ("AND commutes", |vs| eq(and(vs[0], vs[1]), and(vs[1], vs[0]))),
+    ("OR commutes", |vs| eq(or(vs[0], vs[1]), or(vs[1], vs[0]))),
+    ("AND associates", |vs| eq(and(and(vs[0], vs[1]), vs[2]), and(vs[0], and(vs[1], vs[2])))),
+    ("OR associates", |vs| eq(or(or(vs[0], vs[1]), vs[2]), or(vs[0], or(vs[1], vs[2])))),
+    ("absorption", |vs| eq(or(vs[0], and(vs[0], vs[1])), vs[0])),
+    ("material implication", |vs| eq(imply(vs[0], vs[1]), or(not(vs[0]), vs[1]))),
+    ("XOR is not equal", |vs| eq(xor(vs[0], vs[1]), not(eq(vs[0], vs[1])))),
+    ("exportation", |vs| eq(imply(and(vs[0], vs[1]), vs[2]), imply(vs[0], imply(vs[1], vs[2])))),
+    ("Peirce's law", |vs| imply(imply(imply(vs[0], vs[1]), vs[0]), vs[0])),
+];
+
+/// Proves every law in `CLASSICAL_LAWS`.
+///
+/// This is a smoke test to verify that the crate works correctly on a platform.
+/// Returns `false` if any law fails.
+pub fn self_test() -> bool {
+    CLASSICAL_LAWS.iter().all(|&(_, mut f)| proven(3, &mut f))
+}
+
+/// Proves that the rules imply the goal, where propositions are read by index.
+///
+/// This is the same as `proven(props, &mut |vs| imply(rules(vs), goal(vs)))`.
+pub fn proven_vec(
+    props: usize,
+    rules: impl Fn(&[u64]) -> u64,
+    goal: impl Fn(&[u64]) -> u64
+) -> bool {
+    proven(props, &mut |vs| imply(rules(vs), goal(vs)))
+}
+
+/// Ranks candidate premises by how strongly each alone implies a conclusion.
+///
+/// Returns the index of every candidate together with `P(conclusion | candidate)`,
+/// sorted descending by probability.
+/// Candidates that are never true have no probability and are left out.
+///
+/// All candidates are counted in a single pass over the cases.
+pub fn rank_premises(
+    n: usize,
+    candidates: &[Box<PredN>],
+    conclusion: &PredN
+) -> Vec<(usize, f64)> {
+    let mut counts = vec![(0, 0); candidates.len()];
+    let mut args = vec![0; n];
+    for chunk in 0..chunks(n) {
+        let c = eval_chunk(n, chunk, &mut args, &mut |vs| conclusion(vs));
+        for (count, candidate) in counts.iter_mut().zip(candidates) {
+            let a = eval_chunk(n, chunk, &mut args, &mut |vs| candidate(vs));
+            count.0 += a.count_ones();
+            count.1 += and(a, c).count_ones();
+        }
+    }
+    let mut res: Vec<(usize, f64)> = counts.into_iter().enumerate()
+        .filter(|&(_, (a, _))| a != 0)
+        .map(|(i, (a, ac))| (i, ac as f64 / a as f64))
+        .collect();
+    res.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    res
+}
+
 /// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
 ///
 /// For more information, see the section "Path Semantical Logic" at the top level documentation.
@@ -1228,49 +2718,124 @@ pub fn path1_prove2<F: FnMut(u64, u64) -> u64>(f: &mut F) -> bool {prove2(f)}
 /// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
 ///
 /// For more information, see the section "Path Semantical Logic" at the top level documentation.
-pub fn path1_prove3<F: FnMut((u64, u64), u64) -> u64>(f: &mut F) -> bool {path1_count3(f) == 8}
+pub fn path1_prove3<F: FnMut((u64, u64), u64) -> u64>(f: &mut F) -> bool {
+    path1_count3(f) == path1_lennm(2, 1)
+}
 /// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
 ///
 /// For more information, see the section "Path Semantical Logic" at the top level documentation.
 pub fn path1_prove4<F: FnMut((u64, u64), (u64, u64)) -> u64>(f: &mut F) -> bool {
-    path1_count4(f) == 14
+    path1_count4(f) == path1_lennm(2, 2)
 }
 /// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
 ///
 /// For more information, see the section "Path Semantical Logic" at the top level documentation.
 pub fn path1_prove5<F: FnMut((u64, u64, u64), (u64, u64)) -> u64>(f: &mut F) -> bool {
-    path1_count5(f) == 24
+    path1_count5(f) == path1_lennm(3, 2)
 }
 /// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
 ///
 /// For more information, see the section "Path Semantical Logic" at the top level documentation.
 pub fn path1_prove6<F: FnMut((u64, u64, u64), (u64, u64, u64)) -> u64>(f: &mut F) -> bool {
-    path1_count6(f) == 40
+    path1_count6(f) == path1_lennm(3, 3)
 }
 /// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
 ///
 /// For more information, see the section "Path Semantical Logic" at the top level documentation.
 pub fn path1_prove7<F: FnMut((u64, u64, u64, u64), (u64, u64, u64)) -> u64>(f: &mut F) -> bool {
-    path1_count7(f) == 62
+    path1_count7(f) == path1_lennm(4, 3)
 }
 /// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
 ///
 /// For more information, see the section "Path Semantical Logic" at the top level documentation.
 pub fn path1_prove8<F: FnMut((u64, u64, u64, u64), (u64, u64, u64, u64)) -> u64>(f: &mut F) -> bool {
-    path1_count8(f) == 102
+    path1_count8(f) == path1_lennm(4, 4)
 }
 /// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
 ///
 /// For more information, see the section "Path Semantical Logic" at the top level documentation.
 pub fn path1_prove9<F: FnMut((u64, u64, u64, u64, u64), (u64, u64, u64, u64)) -> u64>(f: &mut F) -> bool {
-    path1_count9(f) == 148
+    path1_count9(f) == path1_lennm(5, 4)
 }
 /// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
 ///
 /// For more information, see the section "Path Semantical Logic" at the top level documentation.
 pub fn path1_prove10<F: FnMut((u64, u64, u64, u64, u64), (u64, u64, u64, u64, u64)) -> u64>(f: &mut F) -> bool {
-    path1_count10(f) == 244
+    path1_count10(f) == path1_lennm(5, 5)
+}
+/// Finds the maximal subsets of rules that are consistent together.
+///
+/// Returns the indices of the rules in every subset, where the rules of a subset
+/// are true together for some case and no other rule can be added to the subset.
+/// This is used to diagnose which rules conflict in an inconsistent rule set.
+///
+/// The rules satisfied by every case are collected in a single pass over the cases,
+/// which supports up to 64 rules.
+pub fn maximal_consistent_subsets(n: usize, rules: &[Box<PredN>]) -> Vec<Vec<usize>> {
+    assert!(rules.len() <= 64, "Expected at most 64 rules");
+    let mut sets = alloc::collections::BTreeSet::new();
+    let mut args = vec![0; n];
+    for chunk in 0..chunks(n) {
+        let columns: Vec<u64> = rules.iter()
+            .map(|rule| eval_chunk(n, chunk, &mut args, &mut |vs| rule(vs)))
+            .collect();
+        for bit in 0..(1u64 << n.min(6)) {
+            sets.insert(columns.iter().enumerate()
+                .fold(0u64, |acc, (i, c)| acc | ((c >> bit) & 1) << i));
+        }
+    }
+    let mut res: Vec<Vec<usize>> = sets.iter()
+        .filter(|&&a| !sets.iter().any(|&b| b != a && a & b == a))
+        .map(|&a| (0..rules.len()).filter(|i| (a >> i) & 1 == 1).collect())
+        .collect();
+    res.sort();
+    res
+}
+
+/// Finds explanations of an observation that the rules do not entail on their own.
+///
+/// Returns the minimal subsets of the pool of assumptions,
+/// such that the rules and the assumptions together entail the observation
+/// while still being consistent.
+/// Subsets are listed by increasing size, which is exponential in the size of the pool.
+///
+/// The columns of the rules, the observation and the pool are computed once,
+/// which uses memory proportional to `2^n` for every assumption.
+pub fn abduce(
+    n: usize,
+    rules: &PredN,
+    pool: &[Box<PredN>],
+    observation: &PredN
+) -> Vec<Vec<usize>> {
+    assert!(pool.len() < 64, "Expected less than 64 assumptions");
+    let mut args = vec![0; n];
+    let mut table = |f: &PredN| -> Vec<u64> {
+        (0..chunks(n)).map(|chunk| eval_chunk(n, chunk, &mut args, &mut |vs| f(vs))).collect()
+    };
+    let rules = table(rules);
+    let observation = table(observation);
+    let pool: Vec<Vec<u64>> = pool.iter().map(|f| table(f)).collect();
+    let mask = if n >= 6 {T} else {(1 << (1 << n)) - 1};
+
+    let mut subsets: Vec<u64> = (0..1u64 << pool.len()).collect();
+    subsets.sort_by_key(|s| s.count_ones());
+    let mut res: Vec<u64> = vec![];
+    for s in subsets {
+        if res.iter().any(|r| r & !s == 0) {continue}
+        let mut consistent = false;
+        let mut entails = true;
+        for chunk in 0..rules.len() {
+            let assumed = pool.iter().enumerate()
+                .filter(|&(i, _)| (s >> i) & 1 == 1)
+                .fold(rules[chunk], |acc, (_, p)| and(acc, p[chunk]));
+            consistent |= assumed != 0;
+            entails &= imply(assumed, observation[chunk]) & mask == mask;
+        }
+        if consistent && entails {res.push(s)}
+    }
+    res.into_iter().map(|s| (0..pool.len()).filter(|i| (s >> i) & 1 == 1).collect()).collect()
 }
+
 /// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
 ///
 /// For more information, see the section "Path Semantical Logic" at the top level documentation.
@@ -1297,10 +2862,216 @@ pub fn path1_provenm<F: FnMut(&[u64], &[u64]) -> u64>(f: usize, x: usize, fun: &
     path1_countnm(f, x, fun) == path1_lennm(f, x)
 }
 
+/// Path Semantical Logic: Computes number of cases for level 2, 1 and 0.
+///
+/// The arguments `f`, `g` and `x` are the number of propositions at level 2, 1 and 0.
+/// Each pair of neighbouring levels uses the same cases as `path1_countnm`:
+/// When two or more propositions of a level are zero,
+/// the propositions of the level below are either all zero or all one.
+///
+/// The cases of the middle level are enumerated one by one,
+/// so `g` should be small (see `pathn_countnm`).
+pub fn path2_countnm<F: FnMut(&[u64], &[u64], &[u64]) -> u64>(
+    f: usize,
+    g: usize,
+    x: usize,
+    fun: &mut F
+) -> u64 {
+    pathn_countnm(&[f, g, x], &mut |ls| fun(ls[0], ls[1], ls[2]))
+}
+
+/// Path Semantical Logic: Computes number of cases for level 2, 1 and 0.
+///
+/// This is `2^f * (2^x + 2) + (1 + f) * (g * 2^x + 2 * (2^g - 2 - g))`
+/// for `g >= 2` and `x >= 1`, e.g. `path2_lennm(3, 2, 1) == 48`.
+/// With `g < 2`, the levels 2 and 0 are independent of each other.
+pub fn path2_lennm(f: usize, g: usize, x: usize) -> u64 {
+    let pow = |n: usize| 2_u64.pow(n as u32);
+    match g {
+        0 => pow(f + x),
+        1 => pow(1 + f + x),
+        _ => {
+            // Number of cases where `xs` are constant.
+            let c = pow(x).min(2);
+            pow(f) * (pow(x) + c) + (1 + f as u64) * (g as u64 * pow(x) + c * (pow(g) - 2 - g as u64))
+        }
+    }
+}
+
+/// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
+///
+/// For more information, see `path2_countnm`.
+pub fn path2_provenm<F: FnMut(&[u64], &[u64], &[u64]) -> u64>(
+    f: usize,
+    g: usize,
+    x: usize,
+    fun: &mut F
+) -> bool {
+    path2_countnm(f, g, x, fun) == path2_lennm(f, g, x)
+}
+
+/// Path Semantical Logic: Computes number of cases for any number of levels.
+///
+/// The `levels` are the number of propositions at each level, from the highest level to level 0.
+/// Each pair of neighbouring levels uses the same cases as `path1_countnm`:
+/// When two or more propositions of a level are zero,
+/// the propositions of the level below are either all zero or all one.
+///
+/// The cases of the levels between the highest level and level 0 are enumerated one by one,
+/// so these levels should be small.
+pub fn pathn_countnm(levels: &[usize], fun: &mut dyn FnMut(&[&[u64]]) -> u64) -> u64 {
+    fn constant(vs: &[u64]) -> bool {vs.iter().all(|&v| v == vs[0])}
+    fn few_zeros(vs: &[u64]) -> bool {vs.iter().filter(|&&v| v == F).count() <= 1}
+    fn args<'a>(fs: &'a [u64], ms: &[&'a [u64]], xs: &'a [u64]) -> Vec<&'a [u64]> {
+        [&[fs], ms, &[xs]].concat()
+    }
+
+    let n = levels.len();
+    match n {
+        0 => return countn(0, &mut |_| fun(&[])),
+        1 => return countn(levels[0], &mut |vs| fun(&[vs])),
+        2 => return path1_countnm(levels[0], levels[1], &mut |fs, xs| fun(&[fs, xs])),
+        _ => {}
+    }
+    let (f, x) = (levels[0], levels[n - 1]);
+    // Without `xs`, there is one case where `xs` are constant.
+    let constant_xs: &[u64] = if x == 0 {&[T]} else {&[F, T]};
+    let m: usize = levels[1..n - 1].iter().sum();
+    let mut gs = vec![0; m];
+    let mut sum = 0;
+    for i in 0..1_u64 << m {
+        // The bits of `i` are the zeros in the levels between.
+        for (j, v) in gs.iter_mut().enumerate() {*v = if (i >> j) & 1 == 1 {F} else {T}}
+        let mut rest = &*gs;
+        let ms: Vec<&[u64]> = levels[1..n - 1].iter().map(|&k| {
+            let (a, b) = rest.split_at(k);
+            rest = b;
+            a
+        }).collect();
+        if !ms.windows(2).all(|w| few_zeros(w[0]) || constant(w[1])) {continue}
+        // Constant level below allows any `fs`, otherwise there is at most one zero in `fs`.
+        let any_fs = constant(ms[0]);
+        // At most one zero in level above allows any `xs`, otherwise `xs` are constant.
+        let any_xs = few_zeros(ms[ms.len() - 1]);
+        sum += match (any_fs, any_xs) {
+            (true, true) => countn(f + x, &mut |vs| fun(&args(&vs[..f], &ms, &vs[f..]))),
+            (true, false) => constant_xs.iter().map(|&v| {
+                let xs = vec![v; x];
+                countn(f, &mut |fs| fun(&args(fs, &ms, &xs)))
+            }).sum(),
+            (false, true) => (0..=f).map(|k| {
+                let mut fs = vec![T; f];
+                if k < f {fs[k] = F}
+                countn(x, &mut |xs| fun(&args(&fs, &ms, xs)))
+            }).sum(),
+            (false, false) => (0..=f).map(|k| {
+                let mut fs = vec![T; f];
+                if k < f {fs[k] = F}
+                constant_xs.iter().map(|&v| {
+                    let xs = vec![v; x];
+                    countn(0, &mut |_| fun(&args(&fs, &ms, &xs)))
+                }).sum::<u64>()
+            }).sum(),
+        };
+    }
+    sum
+}
+
+/// Path Semantical Logic: Computes number of cases for any number of levels.
+///
+/// For two levels this is the same as `path1_lennm`, and for three levels `path2_lennm`.
+pub fn pathn_lennm(levels: &[usize]) -> u64 {
+    // Counts the cases of a level by whether it is constant and has at most one zero.
+    fn classes(n: usize) -> [((bool, bool), u64); 4] {
+        match n {
+            0 => [((true, true), 1), ((true, false), 0), ((false, true), 0), ((false, false), 0)],
+            1 => [((true, true), 2), ((true, false), 0), ((false, true), 0), ((false, false), 0)],
+            _ => [
+                ((true, true), 1),
+                ((true, false), 1),
+                ((false, true), n as u64),
+                ((false, false), 2_u64.pow(n as u32) - 2 - n as u64),
+            ],
+        }
+    }
+
+    let mut counts = [0; 4];
+    for (i, &n) in levels.iter().enumerate() {
+        let cl = classes(n);
+        if i == 0 {
+            for (c, &(_, k)) in counts.iter_mut().zip(cl.iter()) {*c = k}
+            continue;
+        }
+        let prev = counts;
+        let above = classes(levels[i - 1]);
+        for (c, &((constant, _), k)) in counts.iter_mut().zip(cl.iter()) {
+            *c = k * prev.iter().zip(above.iter())
+                .filter(|&(_, &((_, few_zeros), _))| few_zeros || constant)
+                .map(|(&p, _)| p).sum::<u64>();
+        }
+    }
+    if levels.is_empty() {1} else {counts.iter().sum()}
+}
+
+/// Path Semantical Logic: Returns `true` if proposition is correct, `false` otherwise.
+///
+/// For more information, see `pathn_countnm`.
+pub fn pathn_provenm(levels: &[usize], fun: &mut dyn FnMut(&[&[u64]]) -> u64) -> bool {
+    pathn_countnm(levels, fun) == pathn_lennm(levels)
+}
+
 /// Returns `T` if `a` is `true`, `F` otherwise.
 /// In logical terminology this corresponds to a proposition.
 pub fn prop(a: bool) -> u64 {if a {T} else {F}}
 
+/// A proposition using operators for the logical relations.
+///
+/// The operators `&`, `|`, `^` and `!` are `and`, `or`, `xor` and `not`.
+/// By convention, `>>` is used for `imply`, such that `(a & b) >> c` reads as `imply(and(a, b), c)`.
+/// Dereferences to `u64` for use with `prove!`.
+///
+/// ```rust
+/// use pocket_prover::*;
+///
+/// assert!(prove!(&mut |a, b| *((Prop(a) & (Prop(a) >> Prop(b))) >> Prop(b))));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Prop(pub u64);
+
+impl core::ops::Deref for Prop {
+    type Target = u64;
+    fn deref(&self) -> &u64 {&self.0}
+}
+
+impl From<Prop> for u64 {
+    fn from(a: Prop) -> u64 {a.0}
+}
+
+impl core::ops::Not for Prop {
+    type Output = Prop;
+    fn not(self) -> Prop {Prop(not(self.0))}
+}
+
+impl core::ops::BitAnd for Prop {
+    type Output = Prop;
+    fn bitand(self, other: Prop) -> Prop {Prop(and(self.0, other.0))}
+}
+
+impl core::ops::BitOr for Prop {
+    type Output = Prop;
+    fn bitor(self, other: Prop) -> Prop {Prop(or(self.0, other.0))}
+}
+
+impl core::ops::BitXor for Prop {
+    type Output = Prop;
+    fn bitxor(self, other: Prop) -> Prop {Prop(xor(self.0, other.0))}
+}
+
+impl core::ops::Shr for Prop {
+    type Output = Prop;
+    fn shr(self, other: Prop) -> Prop {Prop(imply(self.0, other.0))}
+}
+
 /// Ignores argument, always returning `false`.
 pub fn false_1(_: u64) -> u64 {0}
 /// If input is `true`, returns `false` and vice versa.
@@ -1322,6 +3093,12 @@ pub fn xor(a: u64, b: u64) -> u64 {a ^ b}
 pub fn eq(a: u64, b: u64) -> u64 {!(a ^ b)}
 /// First argument implies the second.
 pub fn imply(a: u64, b: u64) -> u64 {!a | b}
+/// Returns `true` if not all arguments are `true`.
+pub fn nand(a: u64, b: u64) -> u64 {!(a & b)}
+/// Returns `true` if no argument is `true`.
+pub fn nor(a: u64, b: u64) -> u64 {!(a | b)}
+/// Returns `true` if both arguments are `true` or both are `false`, same as `eq`.
+pub fn xnor(a: u64, b: u64) -> u64 {!(a ^ b)}
 /// Ignores both arguments, returning `true` for all inputs.
 pub fn true_2(_: u64, _: u64) -> u64 {T}
 
@@ -1539,6 +3316,80 @@ pub fn xorn(vs: &[u64]) -> u64 {
     }
 }
 
+/// A NAND relation of 3 arguments.
+pub fn nand3(a: u64, b: u64, c: u64) -> u64 {not(and3(a, b, c))}
+/// A NAND relation of 4 arguments.
+pub fn nand4(a: u64, b: u64, c: u64, d: u64) -> u64 {not(and4(a, b, c, d))}
+/// A NAND relation of 5 arguments.
+pub fn nand5(a: u64, b: u64, c: u64, d: u64, e: u64) -> u64 {not(and5(a, b, c, d, e))}
+/// A NAND relation of 6 arguments.
+pub fn nand6(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64) -> u64 {not(and6(a, b, c, d, e, f))}
+/// A NAND relation of 7 arguments.
+pub fn nand7(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64, g: u64) -> u64 {not(and7(a, b, c, d, e, f, g))}
+/// A NAND relation of 8 arguments.
+pub fn nand8(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64, g: u64, h: u64) -> u64 {not(and8(a, b, c, d, e, f, g, h))}
+/// A NAND relation of 9 arguments.
+pub fn nand9(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64, g: u64, h: u64, i: u64) -> u64 {not(and9(a, b, c, d, e, f, g, h, i))}
+/// A NAND relation of 10 arguments.
+pub fn nand10(
+    a: u64, b: u64, c: u64, d: u64, e: u64,
+    f: u64, g: u64, h: u64, i: u64, j: u64
+) -> u64 {not(and10(a, b, c, d, e, f, g, h, i, j))}
+/// A NAND relation of variable number of arguments.
+pub fn nandn(vs: &[u64]) -> u64 {not(andn(vs))}
+
+/// A NOR relation of 3 arguments.
+pub fn nor3(a: u64, b: u64, c: u64) -> u64 {not(or3(a, b, c))}
+/// A NOR relation of 4 arguments.
+pub fn nor4(a: u64, b: u64, c: u64, d: u64) -> u64 {not(or4(a, b, c, d))}
+/// A NOR relation of 5 arguments.
+pub fn nor5(a: u64, b: u64, c: u64, d: u64, e: u64) -> u64 {not(or5(a, b, c, d, e))}
+/// A NOR relation of 6 arguments.
+pub fn nor6(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64) -> u64 {not(or6(a, b, c, d, e, f))}
+/// A NOR relation of 7 arguments.
+pub fn nor7(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64, g: u64) -> u64 {not(or7(a, b, c, d, e, f, g))}
+/// A NOR relation of 8 arguments.
+pub fn nor8(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64, g: u64, h: u64) -> u64 {not(or8(a, b, c, d, e, f, g, h))}
+/// A NOR relation of 9 arguments.
+pub fn nor9(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64, g: u64, h: u64, i: u64) -> u64 {not(or9(a, b, c, d, e, f, g, h, i))}
+/// A NOR relation of 10 arguments.
+pub fn nor10(
+    a: u64, b: u64, c: u64, d: u64, e: u64,
+    f: u64, g: u64, h: u64, i: u64, j: u64
+) -> u64 {not(or10(a, b, c, d, e, f, g, h, i, j))}
+/// A NOR relation of variable number of arguments.
+pub fn norn(vs: &[u64]) -> u64 {not(orn(vs))}
+
+/// An XNOR relation of 3 arguments.
+pub fn xnor3(a: u64, b: u64, c: u64) -> u64 {not(xor3(a, b, c))}
+/// An XNOR relation of 4 arguments.
+pub fn xnor4(a: u64, b: u64, c: u64, d: u64) -> u64 {not(xor4(a, b, c, d))}
+/// An XNOR relation of 5 arguments.
+pub fn xnor5(a: u64, b: u64, c: u64, d: u64, e: u64) -> u64 {not(xor5(a, b, c, d, e))}
+/// An XNOR relation of 6 arguments.
+pub fn xnor6(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64) -> u64 {not(xor6(a, b, c, d, e, f))}
+/// An XNOR relation of 7 arguments.
+pub fn xnor7(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64, g: u64) -> u64 {not(xor7(a, b, c, d, e, f, g))}
+/// An XNOR relation of 8 arguments.
+pub fn xnor8(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64, g: u64, h: u64) -> u64 {not(xor8(a, b, c, d, e, f, g, h))}
+/// An XNOR relation of 9 arguments.
+pub fn xnor9(a: u64, b: u64, c: u64, d: u64, e: u64, f: u64, g: u64, h: u64, i: u64) -> u64 {not(xor9(a, b, c, d, e, f, g, h, i))}
+/// An XNOR relation of 10 arguments.
+pub fn xnor10(
+    a: u64, b: u64, c: u64, d: u64, e: u64,
+    f: u64, g: u64, h: u64, i: u64, j: u64
+) -> u64 {not(xor10(a, b, c, d, e, f, g, h, i, j))}
+/// An XNOR relation of variable number of arguments.
+pub fn xnorn(vs: &[u64]) -> u64 {not(xorn(vs))}
+
+/// Returns `true` when all arguments are equal, either all `true` or all `false`.
+///
+/// This is the `contr!` relation for a variable number of arguments.
+/// For no arguments, this returns `true`.
+pub fn alleq(vs: &[u64]) -> u64 {
+    or(andn(vs), vs.iter().fold(T, |acc, &v| and(acc, not(v))))
+}
+
 /// An IMPLY chain of 3 arguments.
 pub fn imply3(a: u64, b: u64, c: u64) -> u64 {and(imply(a, b), imply(b, c))}
 /// An IMPLY chain of 4 arguments.
@@ -1594,10 +3445,63 @@ pub fn implyn(vs: &[u64]) -> u64 {
     }
 }
 
+/// Temporal GLOBALLY over a finite trace, where each step is a column.
+///
+/// True when the property holds at every step, same as `andn`.
+pub fn globally(steps: &[u64]) -> u64 {andn(steps)}
+/// Temporal EVENTUALLY over a finite trace, where each step is a column.
+///
+/// True when the property holds at some step, same as `orn`.
+pub fn eventually(steps: &[u64]) -> u64 {orn(steps)}
+/// Temporal UNTIL over a finite trace, where each step is a column.
+///
+/// True when `b` holds at some step and `a` holds at every step before it.
+/// The traces must have the same length.
+pub fn until(a_steps: &[u64], b_steps: &[u64]) -> u64 {
+    assert_eq!(a_steps.len(), b_steps.len(), "Traces must have the same length");
+    a_steps.iter().zip(b_steps).rev().fold(F, |acc, (&a, &b)| or(b, and(a, acc)))
+}
+
+/// Counts the true arguments for every case, returning the count as bit-planes.
+///
+/// Bit `k` of the count for a case is stored in the same bit of `planes[k]`.
+/// The number of planes is the number of bits needed to represent `vs.len()`.
+///
+/// The columns are summed using carry-save addition, 64 cases at a time.
+/// This is the basis for cardinality constraints like "at least `k` of the arguments".
+pub fn column_popcount(vs: &[u64]) -> Vec<u64> {
+    let bits = (usize::BITS - vs.len().leading_zeros()) as usize;
+    let mut planes = vec![0; bits];
+    for &v in vs {
+        let mut carry = v;
+        for plane in planes.iter_mut() {
+            if carry == 0 {break}
+            let sum = *plane ^ carry;
+            carry &= *plane;
+            *plane = sum;
+        }
+    }
+    planes
+}
+
+/// Returns `true` when at least two arguments are `true`.
+pub fn maj(a: u64, b: u64, c: u64) -> u64 {(a & b) | (c & (a | b))}
+/// If `cond` is `true`, returns `a`, otherwise `b`.
+///
+/// This is a multiplexer, choosing between two arguments.
+pub fn ite(cond: u64, a: u64, b: u64) -> u64 {or(and(cond, a), and(not(cond), b))}
+/// A MAJORITY relation of variable number of arguments.
+///
+/// Returns `true` when strictly more than half of the arguments are `true`.
+/// For no arguments, this returns `false`.
+pub fn majn(vs: &[u64]) -> u64 {cardinality::at_least_k(vs, vs.len() as u32 / 2 + 1)}
+
 /// A boolean function of one argument.
 pub type Pred1 = fn(u64) -> u64;
 /// A boolean function (transformed) of two arguments.
 pub type Pred2 = fn(u64, u64) -> u64;
+/// A boolean function of variable number of arguments.
+pub type PredN = dyn Fn(&[u64]) -> u64;
 
 /// Implemented by types to use with `all` and `any`.
 pub trait Enumerable: Sized {
@@ -1620,6 +3524,27 @@ impl Enumerable for Pred1 {
     }
 }
 
+impl Enumerable for Pred2 {
+    fn start() -> Self {false_2}
+    fn inc(&self) -> Option<Self> {
+        fn and_not(a: u64, b: u64) -> u64 {and(a, not(b))}
+        fn not_b(_: u64, b: u64) -> u64 {not(b)}
+        fn not_and(a: u64, b: u64) -> u64 {and(not(a), b)}
+        fn not_a(a: u64, _: u64) -> u64 {not(a)}
+        fn fst(a: u64, _: u64) -> u64 {a}
+        fn imply_rev(a: u64, b: u64) -> u64 {imply(b, a)}
+        fn snd(_: u64, b: u64) -> u64 {b}
+        // Ordered by truth table, where case `i` assigns `a` to bit 0 and `b` to bit 1 of `i`.
+        const TABLE: [Pred2; 16] = [
+            false_2, nor, and_not, not_b, not_and, not_a, xor, nand,
+            and, eq, fst, imply_rev, snd, imply, or, true_2
+        ];
+        // Functions are compared by truth table instead of by address.
+        let i = (self(P0, P1) & 0b1111) as usize;
+        TABLE.get(i + 1).copied()
+    }
+}
+
 impl Enumerable for u8 {
     fn start() -> u8 {0}
     fn inc(&self) -> Option<Self> {self.checked_add(1)}
@@ -1628,21 +3553,101 @@ impl Enumerable for u8 {
 /// Enumerates the type, checking that at least one output is true.
 pub fn any<E: Enumerable + Copy, F: Fn(E) -> u64>(f: &F) -> u64 {
     let mut val = E::start();
-    while let Some(new_val) = E::inc(&val) {
-        if f(new_val) != F {return T};
-        val = new_val;
+    loop {
+        if f(val) != F {return T};
+        match E::inc(&val) {
+            Some(new_val) => val = new_val,
+            None => return F,
+        }
     }
-    F
 }
 
 /// Enumerates the type, checking that all outputs are true.
 pub fn all<E: Enumerable + Copy, F: Fn(E) -> u64>(f: &F) -> u64 {
     let mut val = E::start();
-    while let Some(new_val) = E::inc(&val) {
-        if f(new_val) != T {return F};
-        val = new_val;
+    loop {
+        if f(val) != T {return F};
+        match E::inc(&val) {
+            Some(new_val) => val = new_val,
+            None => return T,
+        }
     }
-    T
+}
+
+/// Combines two statements of a logical system using AND.
+///
+/// This is useful to compose statements passed to `Prove` methods.
+pub fn and_stmt<S, A, B>(a: A, b: B) -> impl Fn(S) -> u64
+    where S: Copy, A: Fn(S) -> u64, B: Fn(S) -> u64
+{
+    move |x| and(a(x), b(x))
+}
+
+/// Combines two statements of a logical system using OR.
+pub fn or_stmt<S, A, B>(a: A, b: B) -> impl Fn(S) -> u64
+    where S: Copy, A: Fn(S) -> u64, B: Fn(S) -> u64
+{
+    move |x| or(a(x), b(x))
+}
+
+/// Combines two statements of a logical system using IMPLY.
+///
+/// For example, `Self::prove(imply_stmt(premise, conclusion))`.
+pub fn imply_stmt<S, A, B>(a: A, b: B) -> impl Fn(S) -> u64
+    where S: Copy, A: Fn(S) -> u64, B: Fn(S) -> u64
+{
+    move |x| imply(a(x), b(x))
+}
+
+/// Negates a statement of a logical system.
+pub fn not_stmt<S, A>(a: A) -> impl Fn(S) -> u64
+    where S: Copy, A: Fn(S) -> u64
+{
+    move |x| not(a(x))
+}
+
+/// Combines many statements of a logical system using AND and evaluates them.
+///
+/// This is useful to aggregate sub-rules in `CoreRules` or `ExtendRules`.
+/// Returns `T` when there are no statements.
+pub fn all_of_fns<S: Copy>(fs: &[&dyn Fn(S) -> u64], x: S) -> u64 {
+    fs.iter().fold(T, |acc, f| and(acc, f(x)))
+}
+
+/// Divides a number of worlds by a total, used by probabilities.
+///
+/// Returns `None` if the total is zero or less than the number of worlds.
+/// The division is done in `f64` after the integer comparison,
+/// so large counts only lose precision in the result.
+fn ratio(part: u64, total: u64) -> Option<f64> {
+    if total == 0 || part > total {None}
+    else {Some(part as f64 / total as f64)}
+}
+
+/// The relationship between an assumption and a conclusion according to the rules.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Relationship {
+    /// The assumption leads to the conclusion, but not to the opposite (see `Prove::means`).
+    Means,
+    /// The assumption leads to the opposite conclusion, but not to the conclusion.
+    MeansNot,
+    /// The assumption leads to neither the conclusion nor the opposite
+    /// (see `Prove::does_not_mean`).
+    Independent,
+    /// The assumption leads to both the conclusion and the opposite,
+    /// which means the assumption can never be true.
+    Contradictory,
+}
+
+/// The effect of adding a rule to a logical system (see `Prove::try_add_rule`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RuleEffect {
+    /// No world is consistent with the rules and the new rule.
+    Inconsistent,
+    /// The rule holds in every world, so adding it changes nothing.
+    NoEffect,
+    /// The rule eliminates some worlds, stores the number of eliminated worlds.
+    Narrowed(u64),
 }
 
 /// Implemented by provable systems of logic.
@@ -1661,7 +3666,7 @@ pub fn all<E: Enumerable + Copy, F: Fn(E) -> u64>(f: &F) -> u64 {
 ///     pub c: u64,
 /// }
 ///
-/// impl ::std::ops::Deref for Foo<Bar> {
+/// impl ::core::ops::Deref for Foo<Bar> {
 ///     type Target = Bar;
 ///     fn deref(&self) -> &Bar {&self.bar}
 /// }
@@ -1686,6 +3691,24 @@ pub trait Prove: Sized + Copy {
         Self::count(f) == Self::count(|_| T)
     }
 
+    /// Returns `true` if the statement holds in some world consistent with the rules.
+    ///
+    /// Since `count` counts `imply(<system>, f)`, the statement should not include the rules.
+    /// The worlds where the rules are false are counted for every statement,
+    /// so the number of worlds where both the rules and the statement are true is `count(f) - count(|_| F)`.
+    fn satisfiable<F: Fn(Self) -> u64>(f: F) -> bool {
+        Self::count(f) > Self::count(|_| F)
+    }
+
+    /// Returns `true` if the statement holds in no world consistent with the rules.
+    ///
+    /// This is the same as proving the negated statement.
+    fn contradiction<F: Fn(Self) -> u64>(f: F) -> bool {!Self::satisfiable(f)}
+
+    /// Returns `true` if the statement holds in every world consistent with the rules,
+    /// same as `prove`.
+    fn tautology<F: Fn(Self) -> u64>(f: F) -> bool {Self::prove(f)}
+
     /// According to the rules, the assumption does not lead to the conclusion,
     /// but neither does it lead to the opposite conclusion.
     fn does_not_mean<F: Fn(Self) -> u64, G: Fn(Self) -> u64>(
@@ -1702,6 +3725,23 @@ pub trait Prove: Sized + Copy {
         !Self::prove(|x| imply(assumption(x), not(conclusion(x))))
     }
 
+    /// Analyzes the relationship between an assumption and a conclusion.
+    ///
+    /// This is more informative than `means` and `does_not_mean`,
+    /// e.g. telling whether `means` failed because the opposite conclusion is derivable.
+    fn relationship<A: Fn(Self) -> u64 + Copy, B: Fn(Self) -> u64 + Copy>(
+        assumption: A, conclusion: B
+    ) -> Relationship {
+        let pos = Self::prove(|x| imply(assumption(x), conclusion(x)));
+        let neg = Self::prove(|x| imply(assumption(x), not(conclusion(x))));
+        match (pos, neg) {
+            (true, false) => Relationship::Means,
+            (false, true) => Relationship::MeansNot,
+            (false, false) => Relationship::Independent,
+            (true, true) => Relationship::Contradictory,
+        }
+    }
+
     /// Proves that according to the rules, two statements are equivalent.
     fn eq<F: Fn(Self) -> u64, G: Fn(Self) -> u64>(a: F, b: G) -> bool {
         Self::prove(|x| eq(a(x), b(x)))
@@ -1720,73 +3760,568 @@ pub trait Prove: Sized + Copy {
         Self::prove(|x| imply(a(x), b(x)))
     }
 
+    /// Proves a statement for every value of an enumerable type.
+    ///
+    /// For example, ranging over `Pred1` proves a statement for all 1-argument predicates.
+    fn prove_forall<E: Enumerable + Copy, G: Fn(Self, E) -> u64>(g: G) -> bool {
+        let mut val = E::start();
+        loop {
+            if !Self::prove(|x| g(x, val)) {return false}
+            match val.inc() {
+                Some(new_val) => val = new_val,
+                None => return true,
+            }
+        }
+    }
+
     /// Computes the logical probability `P(f | rules)`.
+    ///
+    /// Returns `None` when no world is consistent with the rules,
+    /// or when the counts are inconsistent, e.g. by a wrong `count` implementation.
     fn prob<F: Fn(Self) -> u64>(f: F) -> Option<f64> {
         // Get the number of cases when the system implies falsehood.
         // This is an indirect way of counting cases when the rules are true.
         let fa = Self::count(|_| F);
         let tr = Self::count(|_| T);
-        let full_rules = tr - fa;
-        if full_rules == 0 {return None}
-        else {
-            Some((Self::count(f) - fa) as f64 / full_rules as f64)
-        }
+        ratio(Self::count(f).checked_sub(fa)?, tr.checked_sub(fa)?)
     }
 
     /// Computes the logical probability `P(b | a ∧ rules)`.
+    ///
+    /// Returns `None` when no world is consistent with the rules and `a`,
+    /// or when the counts are inconsistent.
     fn prob_imply<A: Fn(Self) -> u64 + Copy, B: Fn(Self) -> u64>(a: A, b: B) -> Option<f64> {
         let fa = Self::count(|_| F);
-        let count_a = Self::count(a) - fa;
-        if count_a == 0 {return None}
-        else {
-            Some((Self::count(|x| and(a(x), b(x))) - fa) as f64 / count_a as f64)
-        }
+        let count_a = Self::count(a).checked_sub(fa)?;
+        ratio(Self::count(|x| and(a(x), b(x))).checked_sub(fa)?, count_a)
     }
-}
 
-impl<T> Prove for T where T: Copy + Construct + ExtendRules {
-    fn count<F: Fn(Self) -> u64>(f: F) -> u64 {
+    /// Computes the logical probability `P(b | conds[0] ∧ conds[1] ∧ ... ∧ rules)`.
+    ///
+    /// This is the same as `prob_under` with the conditions as extra rules.
+    /// Returns `None` when no world is consistent with the rules and the conditions.
+    fn prob_given<B: Fn(Self) -> u64>(conds: &[fn(Self) -> u64], b: B) -> Option<f64> {
+        Self::prob_under(conds, b)
+    }
+
+    /// Computes the joint logical probability `P(a ∧ b | rules)`.
+    fn prob_and<A: Fn(Self) -> u64, B: Fn(Self) -> u64>(a: A, b: B) -> Option<f64> {
+        Self::prob(|x| and(a(x), b(x)))
+    }
+
+    /// Counts `imply(<system>, f(vs))` where `f` reads the arguments by index.
+    ///
+    /// This is used to reason about the arguments constructing the system.
+    fn count_columns<F: Fn(&[u64]) -> u64>(f: F) -> u64 where Self: Construct + ExtendRules {
         countn(<Self as Construct>::n(), &mut |vs| {
             let v: Self = Construct::construct(vs);
-            imply(v.full_rules(), f(v))
+            imply(v.full_rules(), f(vs))
         })
     }
 
-    fn prove<F: Fn(Self) -> u64>(f: F) -> bool {
-        Self::count(f) == 1 << <Self as Construct>::n()
+    /// Proves a statement where `f` reads the arguments by index.
+    fn prove_columns<F: Fn(&[u64]) -> u64>(f: F) -> bool where Self: Construct + ExtendRules {
+        Self::count_columns(f) == 1 << <Self as Construct>::n()
     }
-}
 
-/// Implemented by logical systems to define core rules.
-pub trait CoreRules {
-    /// The core rules of the logical system.
-    fn core_rules(&self) -> u64;
-}
+    /// Partitions the arguments into classes that are equal according to the rules.
+    ///
+    /// Each class is a list of argument indices in increasing order.
+    fn equal_classes() -> Vec<Vec<usize>> where Self: Construct + ExtendRules {
+        let n = <Self as Construct>::n();
+        let mut classes: Vec<Vec<usize>> = vec![];
+        let mut assigned = vec![false; n];
+        for i in 0..n {
+            if assigned[i] {continue}
+            let mut class = vec![i];
+            for j in i + 1..n {
+                if !assigned[j] && Self::prove_columns(|vs| eq(vs[i], vs[j])) {
+                    assigned[j] = true;
+                    class.push(j);
+                }
+            }
+            classes.push(class);
+        }
+        classes
+    }
 
-/// Implemented by logical systems to extend existing ones.
-pub trait ExtendRules: CoreRules {
-    /// The inner logical system.
-    type Inner: ExtendRules;
-    /// Gets the inner logical system.
-    fn inner(&self) -> &Self::Inner;
-    /// Rules used to integrate with inner logical system.
-    fn extend_rules(&self, inner: &Self::Inner) -> u64;
+    /// Finds the arguments that are determined by other arguments according to the rules.
+    ///
+    /// Returns every determined argument with a smallest set of other arguments determining it,
+    /// such that fixing the set fixes the argument in every world consistent with the rules.
+    /// An argument with a constant value is determined by the empty set.
+    /// Arguments that are not determined by all the others are left out.
+    ///
+    /// Sets are searched by increasing size, which is exponential in the number of arguments.
+    fn functional_dependencies() -> Vec<(usize, Vec<usize>)> where Self: Construct + ExtendRules {
+        let n = <Self as Construct>::n();
+        let worlds: Vec<u64> = solutions(n, |vs| {
+            let v: Self = Construct::construct(vs);
+            v.full_rules()
+        }).map(|w| w.iter().enumerate().fold(0, |acc, (i, &x)| acc | (x as u64) << i)).collect();
+        let determines = |set: u64, i: usize| {
+            let mut seen = alloc::collections::BTreeMap::new();
+            worlds.iter().all(|w| *seen.entry(w & set).or_insert((w >> i) & 1) == (w >> i) & 1)
+        };
+        let mut res = vec![];
+        for i in 0..n {
+            let others: Vec<usize> = (0..n).filter(|&j| j != i).collect();
+            let mut subsets: Vec<u64> = (0..1u64 << others.len()).collect();
+            subsets.sort_by_key(|s| s.count_ones());
+            let found = subsets.into_iter()
+                .map(|s| others.iter().enumerate().filter(|&(k, _)| (s >> k) & 1 == 1)
+                    .map(|(_, &j)| j).collect::<Vec<usize>>())
+                .find(|set| determines(set.iter().fold(0, |acc, &j| acc | 1 << j), i));
+            if let Some(set) = found {res.push((i, set))}
+        }
+        res
+    }
 
-    /// The full rules of the entire logical system.
-    fn full_rules(&self) -> u64 {
-        and3(self.core_rules(), self.extend_rules(self.inner()), self.inner().full_rules())
+    /// Merges arguments that are equal according to the rules (see `QuotientSystem`).
+    fn quotient() -> QuotientSystem<Self> where Self: Construct + ExtendRules {
+        QuotientSystem::new()
     }
-}
 
-/// Implemented by base logical systems.
-///
-/// Auto impls the `ExtendRules` trait and therefore also the `Prove` trait.
-pub trait BaseSystem: Construct + CoreRules {}
+    /// Counts the worlds that are consistent with the rules.
+    fn world_count() -> u64 {
+        Self::count(|_| T) - Self::count(|_| F)
+    }
 
-impl<T> ExtendRules for T where T: BaseSystem {
-    type Inner = ();
-    fn inner(&self) -> &() {&()}
-    fn extend_rules(&self, _: &Self::Inner) -> u64 {T}
+    /// Returns `true` if there is at least one world consistent with the rules.
+    fn consistent() -> bool {
+        Self::world_count() > 0
+    }
+
+    /// Checks the effect of adding a rule before extending the system with it.
+    ///
+    /// The rule might make the system inconsistent, have no effect,
+    /// or eliminate some of the worlds consistent with the rules (see `RuleEffect`).
+    fn try_add_rule<R: Fn(Self) -> u64>(rule: R) -> RuleEffect {
+        let worlds = Self::world_count();
+        let with_rule = Self::count(rule) - Self::count(|_| F);
+        if with_rule == 0 {RuleEffect::Inconsistent}
+        else if with_rule == worlds {RuleEffect::NoEffect}
+        else {RuleEffect::Narrowed(worlds - with_rule)}
+    }
+
+    /// Lazily enumerates the worlds consistent with the rules where `filter` is true.
+    ///
+    /// Each world is constructed from arguments that are either `T` or `F` (see `solutions`),
+    /// such that only the cases needed for the taken worlds are evaluated.
+    fn models_iter<'a, F: Fn(Self) -> u64 + 'a>(filter: F) -> Models<'a, Self>
+        where Self: Construct + ExtendRules + 'a
+    {
+        let fun: ModelsFn<'a> = Box::new(move |vs| {
+            let v: Self = Construct::construct(vs);
+            and(v.full_rules(), filter(v))
+        });
+        Models {solutions: solutions(<Self as Construct>::n(), fun), _s: core::marker::PhantomData}
+    }
+
+    /// Checks that the rules are consistent for every round of qubits.
+    ///
+    /// Systems using path semantical core axioms (e.g. `ps_core`) as rules
+    /// might collapse to no consistent worlds for some rounds,
+    /// such that every statement is vacuously proven.
+    /// Use this check before proving anything in such systems.
+    fn check_ps_consistency(rounds: u32) -> bool {
+        measure(rounds, Self::consistent)
+    }
+
+    /// Counts the worlds that are consistent with the rules, grouped by a key.
+    ///
+    /// Each world is constructed from arguments that are either `T` or `F`.
+    /// The counts add up to `world_count`.
+    ///
+    /// Requires the `std` feature.
+    #[cfg(feature = "std")]
+    fn count_by<K, F>(key: F) -> std::collections::HashMap<K, u64>
+        where K: Eq + core::hash::Hash, F: Fn(Self) -> K, Self: Construct + ExtendRules
+    {
+        let n = <Self as Construct>::n();
+        let mut res = std::collections::HashMap::new();
+        for world in solutions(n, |vs| {
+            let v: Self = Construct::construct(vs);
+            v.full_rules()
+        }) {
+            let vs: Vec<u64> = world.into_iter().map(|x| if x {T} else {F}).collect();
+            *res.entry(key(Construct::construct(&vs))).or_insert(0) += 1;
+        }
+        res
+    }
+
+    /// Counts how many worlds each rule eliminates on its own.
+    ///
+    /// Returns `2^n - count` for every rule, where `count` is the number of cases
+    /// where the rule is true, ignoring the rules of the system.
+    /// This shows which rules are doing the heavy constraining.
+    /// All rules are counted in a single pass over the cases.
+    fn rule_eliminations(rules: &[Box<dyn Fn(Self) -> u64>]) -> Vec<u64> where Self: Construct {
+        let n = <Self as Construct>::n();
+        let mut res = vec![1 << n; rules.len()];
+        let mut args = vec![0; n];
+        for chunk in 0..chunks(n) {
+            for (r, rule) in res.iter_mut().zip(rules) {
+                *r -= eval_chunk(n, chunk, &mut args, &mut |vs| rule(Construct::construct(vs)))
+                    .count_ones() as u64;
+            }
+        }
+        res
+    }
+
+    /// Describes the system as a human readable summary of named rules.
+    ///
+    /// Lists every rule with the number of worlds it admits and eliminates on its own
+    /// (see `rule_eliminations`), followed by the number of worlds consistent with the rules.
+    fn describe(names: &[&str], rules: &[Box<dyn Fn(Self) -> u64>]) -> String
+        where Self: Construct + ExtendRules
+    {
+        use core::fmt::Write;
+
+        assert_eq!(names.len(), rules.len(), "Expected a name for every rule");
+        let n = <Self as Construct>::n();
+        let mut s = String::new();
+        for (name, elim) in names.iter().zip(Self::rule_eliminations(rules)) {
+            writeln!(s, "{}: admits {}, eliminates {}", name, (1u64 << n) - elim, elim).unwrap();
+        }
+        writeln!(s, "Models: {} of {}", Self::world_count(), 1u64 << n).unwrap();
+        s
+    }
+
+    /// Computes a hash of the models of the system.
+    ///
+    /// Systems with the same number of arguments and the same models have the same fingerprint,
+    /// no matter how their rules are written.
+    /// This is used to cache results by the meaning of a system rather than by its type.
+    ///
+    /// Requires the `std` feature.
+    #[cfg(feature = "std")]
+    fn fingerprint() -> u64 where Self: Construct + ExtendRules {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hasher;
+
+        let n = <Self as Construct>::n();
+        let mut hasher = DefaultHasher::new();
+        hasher.write_usize(n);
+        let mut args = vec![0; n];
+        for chunk in 0..chunks(n) {
+            hasher.write_u64(eval_chunk(n, chunk, &mut args, &mut |vs| {
+                let v: Self = Construct::construct(vs);
+                v.full_rules()
+            }));
+        }
+        hasher.finish()
+    }
+
+    /// Computes the logical probability `P(f | extra ∧ rules)` using extra rules.
+    fn prob_under<R: Fn(Self) -> u64, F: Fn(Self) -> u64>(extra: &[R], f: F) -> Option<f64> {
+        Self::prob_imply(|x| extra.iter().fold(T, |acc, r| and(acc, r(x))), f)
+    }
+
+    /// Computes how much a rule changes the logical probability of a statement.
+    ///
+    /// This is `P(f | rule ∧ rules) - P(f | rules)`.
+    /// A positive number means the rule makes the statement more likely.
+    fn rule_sensitivity<R: Fn(Self) -> u64, F: Fn(Self) -> u64>(rule: R, f: F) -> Option<f64> {
+        Some(Self::prob_under(&[rule], &f)? - Self::prob(&f)?)
+    }
+
+    /// Estimates the logical probability `P(f | rules)` from random worlds.
+    ///
+    /// Samples worlds uniformly, 64 at a time, and keeps those consistent with the rules.
+    /// This is used for systems with too many arguments to compute `prob` exactly,
+    /// and converges to `prob` as the number of samples grows.
+    /// Returns `None` when no sampled world is consistent with the rules.
+    ///
+    /// Requires the `quality` feature.
+    #[cfg(feature = "quality")]
+    fn prob_approx<F: Fn(Self) -> u64, R: rand::Rng>(f: F, samples: usize, rng: &mut R) -> Option<f64>
+        where Self: Construct + ExtendRules
+    {
+        let n = <Self as Construct>::n();
+        let mut args = vec![0; n];
+        let (mut consistent, mut hits) = (0, 0);
+        for i in (0..samples).step_by(64) {
+            for arg in &mut args {*arg = rng.gen()}
+            let mask = if samples - i >= 64 {T} else {(1 << (samples - i)) - 1};
+            call(|| {
+                let v: Self = Construct::construct(&args);
+                let rules = v.full_rules() & mask;
+                consistent += rules.count_ones();
+                hits += and(rules, f(v)).count_ones();
+                T
+            });
+        }
+        if consistent == 0 {None} else {Some(hits as f64 / consistent as f64)}
+    }
+}
+
+impl<T> Prove for T where T: Copy + Construct + ExtendRules {
+    fn count<F: Fn(Self) -> u64>(f: F) -> u64 {
+        #[cfg(all(debug_assertions, feature = "std"))]
+        assert!(validate_construct_once::<Self>(),
+            "`construct` reads more arguments than `n()` reports");
+        countn(<Self as Construct>::n(), &mut |vs| {
+            let v: Self = Construct::construct(vs);
+            imply(v.full_rules(), f(v))
+        })
+    }
+
+    fn prove<F: Fn(Self) -> u64>(f: F) -> bool {
+        Self::count(f) == 1 << <Self as Construct>::n()
+    }
+}
+
+/// Stores the models of a logical system to answer many queries quickly.
+///
+/// The `Prove` methods enumerate the rules of the system for every query.
+/// A session enumerates the rules once and stores a bit for every case,
+/// such that queries only evaluate the statement and mask against the stored bits.
+///
+/// The memory used is `2^n / 8` bytes, which is suitable for `n <= 26` or so.
+pub struct ProveSession<S> {
+    rules: Vec<u64>,
+    models: u64,
+    _system: core::marker::PhantomData<S>,
+}
+
+impl<S> ProveSession<S> where S: Copy + Construct + ExtendRules {
+    /// Creates a new session by enumerating the rules.
+    pub fn new() -> ProveSession<S> {
+        let n = <S as Construct>::n();
+        let mut args = vec![0; n];
+        let rules: Vec<u64> = (0..chunks(n)).map(|chunk| eval_chunk(n, chunk, &mut args, &mut |vs| {
+            let v: S = Construct::construct(vs);
+            v.full_rules()
+        })).collect();
+        let models = rules.iter().map(|r| r.count_ones() as u64).sum();
+        ProveSession {rules, models, _system: core::marker::PhantomData}
+    }
+
+    /// Gets the number of cases where the rules are true.
+    pub fn models(&self) -> u64 {self.models}
+
+    /// Counts the cases where the rules and the statement are true.
+    fn count_models<F: Fn(S) -> u64>(&self, f: F) -> u64 {
+        let n = <S as Construct>::n();
+        let mut args = vec![0; n];
+        self.rules.iter().enumerate().map(|(chunk, &r)| {
+            and(r, eval_chunk(n, chunk as u64, &mut args, &mut |vs| f(Construct::construct(vs))))
+                .count_ones() as u64
+        }).sum()
+    }
+
+    /// Counts true statements, same as `Prove::count`.
+    pub fn count<F: Fn(S) -> u64>(&self, f: F) -> u64 {
+        let n = <S as Construct>::n();
+        (1 << n) - self.models + self.count_models(f)
+    }
+
+    /// Proves a statement according to the rules, same as `Prove::prove`.
+    pub fn prove<F: Fn(S) -> u64>(&self, f: F) -> bool {
+        self.count_models(f) == self.models
+    }
+
+    /// Same as `Prove::does_not_mean`.
+    pub fn does_not_mean<F: Fn(S) -> u64, G: Fn(S) -> u64>(&self, assumption: F, conclusion: G) -> bool {
+        self.relationship(assumption, conclusion) == Relationship::Independent
+    }
+
+    /// Same as `Prove::means`.
+    pub fn means<F: Fn(S) -> u64, G: Fn(S) -> u64>(&self, assumption: F, conclusion: G) -> bool {
+        self.relationship(assumption, conclusion) == Relationship::Means
+    }
+
+    /// Same as `Prove::relationship`.
+    pub fn relationship<F: Fn(S) -> u64, G: Fn(S) -> u64>(
+        &self, assumption: F, conclusion: G
+    ) -> Relationship {
+        let pos = self.prove(|x| imply(assumption(x), conclusion(x)));
+        let neg = self.prove(|x| imply(assumption(x), not(conclusion(x))));
+        match (pos, neg) {
+            (true, false) => Relationship::Means,
+            (false, true) => Relationship::MeansNot,
+            (false, false) => Relationship::Independent,
+            (true, true) => Relationship::Contradictory,
+        }
+    }
+
+    /// Same as `Prove::eq`.
+    pub fn eq<F: Fn(S) -> u64, G: Fn(S) -> u64>(&self, a: F, b: G) -> bool {
+        self.prove(|x| eq(a(x), b(x)))
+    }
+
+    /// Same as `Prove::exc`.
+    pub fn exc<F: Fn(S) -> u64, G: Fn(S) -> u64>(&self, a: F, b: G) -> bool {
+        self.prove(|x| not(and(a(x), b(x))))
+    }
+
+    /// Same as `Prove::imply`.
+    pub fn imply<F: Fn(S) -> u64, G: Fn(S) -> u64>(&self, a: F, b: G) -> bool {
+        self.prove(|x| imply(a(x), b(x)))
+    }
+
+    /// Same as `Prove::prob`.
+    pub fn prob<F: Fn(S) -> u64>(&self, f: F) -> Option<f64> {
+        if self.models == 0 {None}
+        else {Some(self.count_models(f) as f64 / self.models as f64)}
+    }
+
+    /// Same as `Prove::prob_imply`.
+    pub fn prob_imply<A: Fn(S) -> u64, B: Fn(S) -> u64>(&self, a: A, b: B) -> Option<f64> {
+        let count_a = self.count_models(&a);
+        if count_a == 0 {None}
+        else {Some(self.count_models(|x| and(a(x), b(x))) as f64 / count_a as f64)}
+    }
+}
+
+impl<S> Default for ProveSession<S> where S: Copy + Construct + ExtendRules {
+    fn default() -> ProveSession<S> {ProveSession::new()}
+}
+
+/// A logical system where arguments that are equal according to the rules are merged.
+///
+/// Each class of equal arguments (see `Prove::equal_classes`) is represented by one argument,
+/// which lowers the number of arguments to prove statements about the system.
+/// Every statement is proven in the quotient if and only if it is proven in the original system,
+/// since cases where merged arguments differ are inconsistent with the rules.
+pub struct QuotientSystem<S> {
+    classes: Vec<Vec<usize>>,
+    map: Vec<usize>,
+    _system: core::marker::PhantomData<S>,
+}
+
+impl<S> QuotientSystem<S> where S: Copy + Construct + ExtendRules {
+    /// Creates a new quotient by finding the classes of equal arguments.
+    pub fn new() -> QuotientSystem<S> {
+        let classes = S::equal_classes();
+        let mut map = vec![0; <S as Construct>::n()];
+        for (k, class) in classes.iter().enumerate() {
+            for &i in class {map[i] = k}
+        }
+        QuotientSystem {classes, map, _system: core::marker::PhantomData}
+    }
+
+    /// Gets the number of arguments of the quotient.
+    pub fn n(&self) -> usize {self.classes.len()}
+
+    /// Gets the classes of merged arguments.
+    ///
+    /// The representative of a class is its first argument.
+    pub fn classes(&self) -> &[Vec<usize>] {&self.classes}
+
+    /// Gets the quotient argument that an argument of the original system is merged into.
+    pub fn class_of(&self, i: usize) -> usize {self.map[i]}
+
+    /// Expands quotient arguments to arguments of the original system.
+    pub fn expand(&self, vs: &[u64]) -> Vec<u64> {
+        self.map.iter().map(|&k| vs[k]).collect()
+    }
+
+    /// Counts true statements, same as `Prove::count`.
+    ///
+    /// The count is over `2^n` cases of the quotient arguments.
+    pub fn count<F: Fn(S) -> u64>(&self, f: F) -> u64 {
+        countn(self.n(), &mut |vs| {
+            let v: S = Construct::construct(&self.expand(vs));
+            imply(v.full_rules(), f(v))
+        })
+    }
+
+    /// Proves a statement according to the rules, same as `Prove::prove`.
+    pub fn prove<F: Fn(S) -> u64>(&self, f: F) -> bool {
+        self.count(f) == 1 << self.n()
+    }
+}
+
+impl<S> Default for QuotientSystem<S> where S: Copy + Construct + ExtendRules {
+    fn default() -> QuotientSystem<S> {QuotientSystem::new()}
+}
+
+/// Returns `true` if two logical systems over the same arguments have different models.
+///
+/// This happens when there is a case consistent with the rules of one system,
+/// but not with the rules of the other.
+/// Stops at the first chunk of cases where the systems differ.
+pub fn systems_differ<A, B>() -> bool
+    where A: Construct + ExtendRules, B: Construct + ExtendRules
+{
+    let n = <A as Construct>::n();
+    assert_eq!(n, <B as Construct>::n(), "Systems must have the same number of arguments");
+    let mut args = vec![0; n];
+    (0..chunks(n)).any(|chunk| eval_chunk(n, chunk, &mut args, &mut |vs| {
+        let a: A = Construct::construct(vs);
+        let b: B = Construct::construct(vs);
+        xor(a.full_rules(), b.full_rules())
+    }) != 0)
+}
+
+/// Computes how much the logical probability of a statement changes from system `A` to `B`.
+///
+/// This is `P(f | rules of B) - P(f | rules of A)`,
+/// where `f` reads the arguments by index (see `Prove::count_columns`),
+/// such that variables correspond across the systems.
+/// Returns `None` when the rules of either system are inconsistent.
+pub fn prob_delta<A, B, F>(f: F) -> Option<f64>
+    where A: Prove + Construct + ExtendRules, B: Prove + Construct + ExtendRules, F: Fn(&[u64]) -> u64
+{
+    assert_eq!(<A as Construct>::n(), <B as Construct>::n(), "Systems must have the same number of arguments");
+    fn prob_columns<S, F>(f: F) -> Option<f64>
+        where S: Prove + Construct + ExtendRules, F: Fn(&[u64]) -> u64
+    {
+        let fa = S::count_columns(|_| F);
+        let full_rules = S::count_columns(|_| T) - fa;
+        if full_rules == 0 {None}
+        else {Some((S::count_columns(f) - fa) as f64 / full_rules as f64)}
+    }
+    Some(prob_columns::<B, _>(&f)? - prob_columns::<A, _>(&f)?)
+}
+
+/// Views the arguments of a system in a permuted order.
+///
+/// Returns a statement reading the arguments by index (see `Prove::count_columns`),
+/// where `f` gets the system constructed from the arguments `perm[0], perm[1], ...`.
+/// This is used to reuse a proof for one system on another with a different argument order.
+pub fn remap<'a, T, F>(perm: &'a [usize], f: F) -> impl Fn(&[u64]) -> u64 + 'a
+    where T: Construct, F: Fn(T) -> u64 + 'a
+{
+    assert_eq!(perm.len(), T::n(), "Permutation must have the same number of arguments as the system");
+    move |vs| {
+        let ws: Vec<u64> = perm.iter().map(|&i| vs[i]).collect();
+        f(T::construct(&ws))
+    }
+}
+
+/// Implemented by logical systems to define core rules.
+pub trait CoreRules {
+    /// The core rules of the logical system.
+    fn core_rules(&self) -> u64;
+}
+
+/// Implemented by logical systems to extend existing ones.
+pub trait ExtendRules: CoreRules {
+    /// The inner logical system.
+    type Inner: ExtendRules;
+    /// Gets the inner logical system.
+    fn inner(&self) -> &Self::Inner;
+    /// Rules used to integrate with inner logical system.
+    fn extend_rules(&self, inner: &Self::Inner) -> u64;
+
+    /// The full rules of the entire logical system.
+    fn full_rules(&self) -> u64 {
+        and3(self.core_rules(), self.extend_rules(self.inner()), self.inner().full_rules())
+    }
+}
+
+/// Implemented by base logical systems.
+///
+/// Auto impls the `ExtendRules` trait and therefore also the `Prove` trait.
+pub trait BaseSystem: Construct + CoreRules {}
+
+impl<T> ExtendRules for T where T: BaseSystem {
+    type Inner = ();
+    fn inner(&self) -> &() {&()}
+    fn extend_rules(&self, _: &Self::Inner) -> u64 {T}
 }
 
 impl CoreRules for () {
@@ -2047,7 +4582,7 @@ pub trait Construct: Sized {
 
     /// Gets the number of bits in logical system.
     fn n() -> usize {
-        use std::mem::size_of;
+        use core::mem::size_of;
         size_of::<Self>() / size_of::<u64>()
     }
 }
@@ -2056,6 +4591,52 @@ impl Construct for () {
     fn construct(_vs: &[u64]) -> Self {()}
 }
 
+/// Implemented by logical systems to name their arguments.
+///
+/// This is used to find the field of an argument index, e.g. in a counterexample.
+/// Can be derived using `pocket_prover-derive`,
+/// where fields of inner systems are named with the field as prefix, e.g. `foo.a`.
+pub trait FieldNames {
+    /// Gets the names of the arguments in argument order.
+    fn field_names() -> Vec<String>;
+}
+
+impl FieldNames for () {
+    fn field_names() -> Vec<String> {vec![]}
+}
+
+/// Returns `true` if `construct` reads no more arguments than `n()` reports.
+///
+/// Constructs the logical system from exactly `n()` arguments,
+/// such that reading past them is caught instead of silently reading adjacent data.
+/// This catches hand-written `Construct` impls that disagree with `n()`.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn validate_construct<T: Construct>() -> bool {
+    let vs = vec![0xdead_beef_dead_beef; <T as Construct>::n()];
+    std::panic::catch_unwind(|| {
+        let _: T = Construct::construct(&vs);
+    }).is_ok()
+}
+
+/// Same as `validate_construct`, but only checks each type once.
+///
+/// This is used to check systems in debug builds without repeating the check on every count.
+#[cfg(all(debug_assertions, feature = "std"))]
+fn validate_construct_once<T: Construct>() -> bool {
+    use std::collections::BTreeSet;
+    use std::sync::Mutex;
+
+    static VALID: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+    let name = core::any::type_name::<T>();
+    if VALID.lock().unwrap().contains(name) {return true}
+    let valid = validate_construct::<T>();
+    if valid {VALID.lock().unwrap().insert(name);}
+    valid
+}
+
 impl<T0: Construct, T1: Construct> Construct for (T0, T1) {
     fn construct(vs: &[u64]) -> Self {
         let n = <T0 as Construct>::n();
@@ -2249,7 +4830,44 @@ impl<T0, T1, T2, T3, T4, T5, T6, T7, T8, T9> Construct for (T0, T1, T2, T3, T4,
     }
 }
 
-#[cfg(test)]
+/// Tests the propositional core, built with `--no-default-features`.
+#[cfg(all(test, not(feature = "quality")))]
+mod core_tests {
+    use super::*;
+
+    /// A small system where `a` implies `b`.
+    #[derive(Copy, Clone)]
+    struct Ab {
+        a: u64,
+        b: u64,
+    }
+
+    impl Construct for Ab {
+        fn construct(vs: &[u64]) -> Self {Ab {a: vs[0], b: vs[1]}}
+    }
+
+    impl CoreRules for Ab {
+        fn core_rules(&self) -> u64 {imply(self.a, self.b)}
+    }
+
+    impl BaseSystem for Ab {}
+
+    #[test]
+    fn core() {
+        assert_eq!(count2(&mut and), 1);
+        assert_eq!(count2(&mut or), 3);
+        assert_eq!(count10(&mut |_, _, _, _, _, _, _, _, _, _| T), 1024);
+        assert_eq!(count!(&mut |a, b, c| and!(a, b, c)), 1);
+        assert!(prove!(&mut |a, b| imply(and(a, b), or(a, b))));
+        assert!(proven(8, &mut |vs| imply(andn(vs), orn(vs))));
+        assert_eq!(countn(12, &mut |vs| vs[11]), 1 << 11);
+        assert!(Ab::prove(|x| imply(x.a, x.b)));
+        assert!(!Ab::prove(|x| imply(x.b, x.a)));
+        assert_eq!(Ab::count(|x| x.a), 2);
+    }
+}
+
+#[cfg(all(test, feature = "quality"))]
 mod tests {
     use super::*;
 
@@ -2278,4 +4896,1332 @@ mod tests {
         assert_eq!(count9(&mut |_, _, _, _, _, _, _, _, _| T), 512);
         assert_eq!(count10(&mut |_, _, _, _, _, _, _, _, _, _| T), 1024);
     }
+
+    /// A small system where `a` implies `b`.
+    #[derive(Copy, Clone)]
+    struct Ab {
+        a: u64,
+        b: u64,
+        c: u64,
+    }
+
+    impl Construct for Ab {
+        fn construct(vs: &[u64]) -> Self {Ab {a: vs[0], b: vs[1], c: vs[2]}}
+    }
+
+    impl CoreRules for Ab {
+        fn core_rules(&self) -> u64 {imply(self.a, self.b)}
+    }
+
+    impl BaseSystem for Ab {}
+
+    /// Reads more arguments than it reports.
+    struct Misaligned {
+        _a: u64,
+        _b: u64,
+    }
+
+    impl Construct for Misaligned {
+        fn construct(vs: &[u64]) -> Self {Misaligned {_a: vs[0], _b: vs[1]}}
+        fn n() -> usize {1}
+    }
+
+    #[test]
+    fn construct_alignment() {
+        assert!(validate_construct::<Ab>());
+        assert!(validate_construct::<(Ab, Ab)>());
+        assert!(!validate_construct::<Misaligned>());
+        #[cfg(debug_assertions)]
+        for _ in 0..2 {
+            assert!(validate_construct_once::<Ab>());
+            assert!(!validate_construct_once::<Misaligned>());
+        }
+    }
+
+    #[test]
+    fn formula_closure() {
+        let a = formula!(|a, b| imply(a, b));
+        let b = formula!(|a, b| imply(b, a));
+        assert!(!proven(2, &mut |vs| a(vs)));
+        let props = [a, b];
+        assert!(proven(2, &mut |vs| eq(andn(&[props[0](vs), props[1](vs)]), eq(vs[0], vs[1]))));
+    }
+
+    /// Same as `Ab`, but with the rule written differently.
+    #[derive(Copy, Clone)]
+    struct AbOr {
+        a: u64,
+        b: u64,
+    }
+
+    impl Construct for AbOr {
+        fn construct(vs: &[u64]) -> Self {AbOr {a: vs[0], b: vs[1]}}
+        fn n() -> usize {3}
+    }
+
+    impl CoreRules for AbOr {
+        fn core_rules(&self) -> u64 {or(not(self.a), self.b)}
+    }
+
+    impl BaseSystem for AbOr {}
+
+    /// Extends `Ab` with `b` implies `c`.
+    #[derive(Copy, Clone)]
+    struct Abc {
+        ab: Ab,
+    }
+
+    impl Construct for Abc {
+        fn construct(vs: &[u64]) -> Self {Abc {ab: Construct::construct(vs)}}
+    }
+
+    impl CoreRules for Abc {
+        fn core_rules(&self) -> u64 {imply(self.ab.b, self.ab.c)}
+    }
+
+    impl ExtendRules for Abc {
+        type Inner = Ab;
+        fn inner(&self) -> &Ab {&self.ab}
+        fn extend_rules(&self, _: &Ab) -> u64 {T}
+    }
+
+    #[test]
+    fn differ() {
+        assert!(!systems_differ::<Ab, AbOr>());
+        assert!(systems_differ::<Ab, Abc>());
+        assert!(systems_differ::<AbOr, Abc>());
+    }
+
+    #[test]
+    fn try_count() {
+        assert_eq!(try_countn(2, &mut |vs| and(vs[0], vs[1])), Ok(1));
+        assert_eq!(try_countn(7, &mut |vs| qubit(vs[6])).map(|_| ()), Ok(()));
+        match try_countn(2, &mut |vs| vs[2]) {
+            Err(SolveError::Panic(msg)) => assert!(msg.contains("out of bounds")),
+            x => panic!("Expected panic error, got {:?}", x),
+        }
+        assert_eq!(try_countn(64, &mut |_| T), Err(SolveError::TooManyArguments(64)));
+        assert_eq!(try_qubit(P0), Err(SolveError::NoRound));
+        assert_eq!(count1(&mut |a| {
+            let x = try_qubit(a).unwrap();
+            eq(x, qubit(a))
+        }), 2);
+    }
+
+    /// Generates qubits using splitmix64 as a counter based random generator.
+    struct SplitMix;
+
+    impl Entropy for SplitMix {
+        fn sample(&mut self, seed: u64) -> u64 {splitmix64(seed)}
+    }
+
+    #[test]
+    fn entropy() {
+        assert!(prove!(&mut |a| eq(qubit_with(&mut StdRngEntropy, a), qubit(a))));
+        assert!(prove!(&mut |a| eq(qubit_with(&mut SplitMix, not(a)), not(qubit_with(&mut SplitMix, a)))));
+        let x = with_round_seed(7, || find_model(1, &mut |vs| qubit_with(&mut SplitMix, vs[0])));
+        let y = with_round_seed(7, || find_model(1, &mut |vs| qubit_with(&mut SplitMix, vs[0])));
+        assert_eq!(x, y);
+    }
+
+    #[test]
+    fn seeded() {
+        let f = || prove!(&mut |a, b| eq(q(a, b), q(b, a)));
+        assert_eq!(measure_seeded(0, 10, f), measure_seeded(0, 10, f));
+        assert!(measure_seeded(0, 10, f));
+        let g = || count4(&mut |a, b, c, d| qubit(or(and(a, b), xor(c, d))));
+        // The minimum of the counts for the seeds `3, 4, 5, 6, 7`.
+        assert_eq!(measure_seeded(3, 5, g), 6);
+        assert_eq!(measure_seeded(3, 1, g), with_round_seed(3, g));
+    }
+
+    #[test]
+    fn homotopy_levels() {
+        // Membership in a proposition forces the members to be equal.
+        assert!(prove!(&mut |a, b, x| imply(and!(is_prop(x, a, b), imply(a, x), imply(b, x)), eq(a, b))));
+        assert!(!prove!(&mut |a, b, x| imply(and(imply(a, x), imply(b, x)), eq(a, b))));
+
+        // Sets have homotopy level 2.
+        let set = || prove!(&mut |a, b, x| {
+            imply(and!(is_set(x, a, b), imply(a, x), imply(b, x), eq(a, b)), hom_eq(2, a, b))
+        });
+        assert!(measure_seeded(0, 20, set));
+        assert!(!measure_seeded(0, 20, || prove!(&mut |a, b| imply(eq(a, b), hom_eq(2, a, b)))));
+
+        // Groupoids have homotopy level 3.
+        let groupoid = || prove!(&mut |a, b, x| {
+            imply(and!(is_groupoid(x, a, b), imply(a, x), imply(b, x), hom_eq(2, a, b)), hom_eq(3, a, b))
+        });
+        assert!(measure_seeded(0, 20, groupoid));
+        assert!(!measure_seeded(0, 20, || prove!(&mut |a, b| imply(hom_eq(2, a, b), hom_eq(3, a, b)))));
+
+        // The n-groupoids and homotopy levels agree with the definitions above.
+        assert!(measure_seeded(0, 20, || prove!(&mut |a, b, x| and!(
+            eq(is_groupoid_n(0, x, a, b), is_set(x, a, b)),
+            eq(is_groupoid_n(1, x, a, b), is_groupoid(x, a, b)),
+            eq(is_hom_lev_n(0, x, a, b), T),
+            eq(is_hom_lev_n(1, x, a, b), is_prop(x, a, b)),
+            eq(is_hom_lev_n(2, x, a, b), is_set(x, a, b)),
+            eq(is_hom_lev_n(3, x, a, b), is_groupoid(x, a, b))
+        ))));
+    }
+
+    #[test]
+    fn hom_levels() {
+        for seed in 0..10 {
+            assert!(with_round_seed(seed, || prove2(&mut |a, b| and!(
+                eq(hom_eq(0, a, b), T),
+                eq(hom_eq(1, a, b), eq(a, b)),
+                eq(hom_eq(2, a, b), and(eq(a, b), eq(qubit(a), qubit(b)))),
+                eq(hom_eq(3, a, b), and3(eq(a, b), eq(qubit(a), qubit(b)), eq(qubit(qubit(a)), qubit(qubit(b))))),
+                eq(hom_imply(2, a, b), and(imply(a, b), imply(qubit(a), qubit(b))))
+            ))));
+        }
+        assert!(measure_seeded(0, 10, || prove!(&mut |a| hom_eq(10, a, a))));
+    }
+
+    #[test]
+    fn aqual_contra_qual() {
+        let f = || prove!(&mut |a, b| and!(
+            eq(aq(a, b), q(not(a), not(b))),
+            eq(aq(a, b), aq(b, a)),
+            not(and(q(a, b), aq(a, b))),
+            not(and(cq(a, b), cq(b, a))),
+            imply(cq(a, b), eq(a, b)),
+            eq(eq(a, b), or!(q(a, b), aq(a, b), cq(a, b), cq(b, a)))
+        ));
+        assert!(measure_seeded(0, 20, f));
+        assert!(!measure_seeded(0, 20, || prove!(&mut |a, b| eq(cq(a, b), cq(b, a)))));
+
+        // Eq-Catuskoti for cube (see the example `eq_catus_cube`).
+        assert!(measure_seeded(0, 20, || prove!(&mut |a, b, c| {
+            eq(
+                and(eq(a, b), eq(a, c)),
+                or!(
+                    and(aq(a, b), aq(a, c)),
+                    and(aq(a, b), cq(a, c)),
+                    and(cq(a, b), aq(a, c)),
+                    and(cq(a, b), cq(a, c)),
+                    and(cq(b, a), cq(c, a)),
+                    and(cq(b, a), q(a, c)),
+                    and(q(a, b), cq(c, a)),
+                    and(q(a, b), q(a, c)),
+                )
+            )
+        })));
+    }
+
+    #[test]
+    fn amplify_stable() {
+        for seed in 0..20 {
+            with_round_seed(seed, || call(|| {
+                let (a, rounds) = amplify_until_stable(P0, 64);
+                assert!(rounds <= 64);
+                assert_eq!(a, amplify(rounds, P0));
+                assert_eq!(a, amplify(rounds + 1, P0));
+                // No bits are lost between rounds.
+                for i in 0..rounds {assert_eq!(amplify(i, P0) & !amplify(i + 1, P0), 0)}
+                assert_eq!(amplify_until_stable(P0, 1), (amplify(1, P0), 1.min(rounds)));
+                assert_eq!(amplify_until_stable(T, 10), (T, 0));
+                assert_eq!(amplify_until_stable(P0, 0), (P0, 0));
+                T
+            }));
+        }
+    }
+
+    #[test]
+    fn solver() {
+        let f = || prove!(&mut |a, b| imply(qubit(a), qubit(b)));
+        let solver = Solver::default().with_seed(42);
+        assert_eq!(solver, Solver {runs: 100, seed: Some(42)});
+        assert_eq!(solver.prob(f), Solver::default().with_seed(42).prob(f));
+        assert_eq!(solver.prove(f), measure_seeded(42, 100, f));
+        let p = solver.prob(f);
+        assert!(p > 0.0 && p < 1.0);
+        assert!(Solver::default().with_runs(10).prove(|| prove!(&mut |a, b| eq(q(a, b), q(b, a)))));
+        assert_eq!(Solver::default().with_runs(10).prob(|| true), 1.0);
+    }
+
+    #[test]
+    fn average() {
+        assert_eq!(measure_avg(1000, || true), 1.0);
+        assert_eq!(measure_avg(10, || false), 0.0);
+        let mut i = 0;
+        assert_eq!(measure_avg(10, || {i += 1; i % 4 == 0}), 0.2);
+        // Both cases of a random qubit are true a quarter of the time.
+        let p = measure_avg(1000, || prove!(&mut |a| qubit(a)));
+        assert!((p - 0.25).abs() < 0.1);
+        assert_eq!(measure(3, || 0.5), 0.5);
+        assert_eq!(measure(0, || 0.5), 1.0);
+    }
+
+    #[test]
+    fn approx() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let f = |x: Abc| imply(x.ab.a, x.ab.c);
+        let g = |x: Abc| x.ab.c;
+        for h in [&f as &dyn Fn(Abc) -> u64, &g] {
+            let exact = Abc::prob(h).unwrap();
+            let approx = Abc::prob_approx(h, 100_000, &mut rng).unwrap();
+            assert!((approx - exact).abs() < 0.01);
+        }
+        assert_eq!(Abc::prob_approx(f, 0, &mut rng), None);
+        assert_eq!(Wide::prob_approx(|w| w.0[39], 1000, &mut rng).map(|p| p > 0.5), Some(true));
+    }
+
+    #[test]
+    fn add_rule() {
+        assert_eq!(Ab::try_add_rule(|x| imply(x.b, x.c)), RuleEffect::Narrowed(2));
+        assert_eq!(Ab::try_add_rule(|x| or(x.a, not(x.a))), RuleEffect::NoEffect);
+        assert_eq!(Ab::try_add_rule(|x| and(x.a, not(x.b))), RuleEffect::Inconsistent);
+        assert_eq!(Abc::try_add_rule(|x| imply(x.ab.a, x.ab.c)), RuleEffect::NoEffect);
+    }
+
+    #[test]
+    fn satisfiable_contradiction() {
+        assert!(Ab::satisfiable(|x| and(x.a, x.c)));
+        assert!(Ab::contradiction(|x| and(x.a, not(x.b))));
+        assert!(!Ab::contradiction(|x| x.a));
+        assert!(Ab::tautology(|x| imply(x.a, x.b)));
+        assert!(!Ab::tautology(|x| x.a));
+        assert!(Abc::contradiction(|x| and(x.ab.a, not(x.ab.c))));
+        assert_eq!(Ab::contradiction(|x| not(x.c)), Ab::prove(|x| x.c));
+    }
+
+    /// A system of 40 arguments with a closed form count,
+    /// where rules are true in half of the worlds and `a` in half of those.
+    #[derive(Copy, Clone)]
+    struct Large {
+        a: u64,
+    }
+
+    impl Prove for Large {
+        fn count<F: Fn(Self) -> u64>(f: F) -> u64 {
+            (1 << 39) + (f(Large {a: P0}) & 0b11).count_ones() as u64 * (1 << 38)
+        }
+    }
+
+    /// A system where `count` is wrong, counting fewer cases for true than for false.
+    #[derive(Copy, Clone)]
+    struct Broken;
+
+    impl Prove for Broken {
+        fn count<F: Fn(Self) -> u64>(f: F) -> u64 {
+            100 - (f(Broken) & 0b11).count_ones() as u64
+        }
+    }
+
+    #[test]
+    fn prob_large() {
+        assert_eq!(Large::prob(|x| x.a), Some(0.5));
+        assert_eq!(Large::prob(|_| T), Some(1.0));
+        assert_eq!(Large::prob_imply(|x| x.a, |x| not(x.a)), Some(0.0));
+        assert_eq!(Broken::prob(|_| T), None);
+        assert_eq!(Broken::prob_imply(|_| T, |_| T), None);
+    }
+
+    #[test]
+    fn prob_conditions() {
+        let a = |x: Abc| or(x.ab.b, x.ab.c);
+        let b = |x: Abc| x.ab.a;
+        let bayes = Abc::prob_and(a, b).unwrap() / Abc::prob(a).unwrap();
+        assert!((bayes - Abc::prob_imply(a, b).unwrap()).abs() < 1e-12);
+
+        assert_eq!(Abc::prob_given(&[|x| x.ab.c, |x| not(x.ab.a)], |x| x.ab.b), Some(0.5));
+        assert_eq!(Abc::prob_given(&[], |x| x.ab.c), Abc::prob(|x| x.ab.c));
+        assert_eq!(Abc::prob_given(&[|x| x.ab.c], b), Abc::prob_imply(|x| x.ab.c, b));
+        assert_eq!(Abc::prob_given(&[|x| x.ab.a, |x| not(x.ab.c)], b), None);
+    }
+
+    #[test]
+    fn prob_delta_rule() {
+        // Adding `b => c` makes `a => c` follow from the rules.
+        let a_imply_c = |vs: &[u64]| imply(vs[0], vs[2]);
+        assert!((prob_delta::<Ab, Abc, _>(a_imply_c).unwrap() - 1.0 / 6.0).abs() < 1e-12);
+        assert!((prob_delta::<Abc, Ab, _>(a_imply_c).unwrap() + 1.0 / 6.0).abs() < 1e-12);
+        assert_eq!(prob_delta::<Ab, AbOr, _>(a_imply_c), Some(0.0));
+    }
+
+    #[test]
+    fn qubit_namespace() {
+        call(|| {
+            let x = with_qubit_namespace(1, || q(P0, P0));
+            let y = with_qubit_namespace(1, || q(P0, P0));
+            let z = with_qubit_namespace(2, || q(P0, P0));
+            assert_eq!(x, y);
+            assert_ne!(x, z);
+            assert_eq!(q(P0, P0), with_qubit_namespace(0, || q(P0, P0)));
+            T
+        });
+    }
+
+    #[test]
+    fn sensitivity() {
+        assert_eq!(Ab::rule_sensitivity(|x: Ab| x.c, |x: Ab| x.c), Some(0.5));
+        let s = Ab::rule_sensitivity(|x: Ab| x.a, |x: Ab| x.b).unwrap();
+        assert!((s - 1.0 / 3.0).abs() < 1e-10);
+        assert_eq!(Ab::rule_sensitivity(|x: Ab| x.a, |x: Ab| x.c), Some(0.0));
+        assert_eq!(Ab::rule_sensitivity(|_: Ab| F, |x: Ab| x.c), None);
+    }
+
+    /// A system where `a` equals `c`.
+    #[derive(Copy, Clone)]
+    struct AcEq {
+        a: u64,
+        c: u64,
+    }
+
+    impl Construct for AcEq {
+        fn construct(vs: &[u64]) -> Self {AcEq {a: vs[0], c: vs[2]}}
+        fn n() -> usize {3}
+    }
+
+    impl CoreRules for AcEq {
+        fn core_rules(&self) -> u64 {eq(self.a, self.c)}
+    }
+
+    impl BaseSystem for AcEq {}
+
+    #[test]
+    fn classes() {
+        assert_eq!(Ab::equal_classes(), vec![vec![0], vec![1], vec![2]]);
+        assert_eq!(AcEq::equal_classes(), vec![vec![0, 2], vec![1]]);
+    }
+
+    /// A system where `c` is the XOR of `a` and `b`, while `d` is true.
+    #[derive(Copy, Clone)]
+    struct XorAb {
+        a: u64,
+        b: u64,
+        c: u64,
+        d: u64,
+    }
+
+    impl Construct for XorAb {
+        fn construct(vs: &[u64]) -> Self {XorAb {a: vs[0], b: vs[1], c: vs[2], d: vs[3]}}
+    }
+
+    impl CoreRules for XorAb {
+        fn core_rules(&self) -> u64 {and(eq(self.c, xor(self.a, self.b)), self.d)}
+    }
+
+    impl BaseSystem for XorAb {}
+
+    #[test]
+    fn functional_dependencies() {
+        assert_eq!(XorAb::functional_dependencies(), vec![
+            (0, vec![1, 2]),
+            (1, vec![0, 2]),
+            (2, vec![0, 1]),
+            (3, vec![]),
+        ]);
+        assert_eq!(Ab::functional_dependencies(), vec![]);
+    }
+
+    #[test]
+    fn quotient() {
+        let q = AcEq::quotient();
+        assert_eq!(q.n(), <AcEq as Construct>::n() - 1);
+        assert_eq!(q.class_of(2), 0);
+        assert_eq!(q.expand(&[P0, P1]), vec![P0, P1, P0]);
+
+        let stmts: Vec<Box<dyn Fn(AcEq) -> u64>> = vec![
+            Box::new(|x| eq(x.a, x.c)),
+            Box::new(|x| x.a),
+            Box::new(|x| imply(x.a, x.c)),
+            Box::new(|x| and(x.a, not(x.c))),
+            Box::new(|x| or(x.c, not(x.a))),
+            Box::new(|_| F),
+        ];
+        for f in &stmts {
+            assert_eq!(q.prove(f), AcEq::prove(f));
+        }
+        assert!(q.prove(|x| imply(x.a, x.c)));
+        assert!(!q.prove(|x| x.a));
+    }
+
+    #[test]
+    fn stmt_combinators() {
+        let premise = and_stmt(|x: Ab| x.a, |x: Ab| imply(x.b, x.c));
+        assert!(Ab::prove(imply_stmt(premise, |x: Ab| x.c)));
+        assert!(!Ab::prove(imply_stmt(|x: Ab| x.a, |x: Ab| x.c)));
+        assert!(Ab::prove(or_stmt(not_stmt(|x: Ab| x.a), |x: Ab| x.b)));
+    }
+
+    #[test]
+    fn confidence() {
+        assert!((z_score(0.95) - 1.96).abs() < 0.01);
+        assert_eq!(measure_to_confidence(|| prove!(&mut |a| imply(a, a)), 0.95, 0.05, 1000), Some((1.0, 30)));
+        let (p, rounds) = measure_to_confidence(rand::random::<bool>, 0.95, 0.05, 100_000).unwrap();
+        assert!(rounds > 300);
+        assert!((p - 0.5).abs() < 0.1);
+        assert_eq!(measure_to_confidence(rand::random::<bool>, 0.95, 0.001, 1000), None);
+        assert_eq!(measure_to_confidence(|| true, 0.95, 0.05, 10), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn confidence_certain() {
+        measure_to_confidence(|| true, 1.0, 0.05, 1000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn confidence_no_margin() {
+        measure_to_confidence(|| true, 0.95, 0.0, 1000);
+    }
+
+    /// Same as `Ab`, but with the first two arguments swapped.
+    #[derive(Copy, Clone)]
+    struct Ba {
+        b: u64,
+        a: u64,
+    }
+
+    impl Construct for Ba {
+        fn construct(vs: &[u64]) -> Self {Ba {b: vs[0], a: vs[1]}}
+        fn n() -> usize {3}
+    }
+
+    impl CoreRules for Ba {
+        fn core_rules(&self) -> u64 {imply(self.a, self.b)}
+    }
+
+    impl BaseSystem for Ba {}
+
+    #[test]
+    fn remap_proof() {
+        let proof = |x: Ab| imply(and(x.a, x.c), x.b);
+        assert!(Ab::prove(proof));
+        assert!(Ba::prove_columns(remap(&[1, 0, 2], proof)));
+        assert!(!Ba::prove_columns(remap(&[0, 1, 2], proof)));
+    }
+
+    /// Operators interpreted by the naive oracle.
+    #[derive(Copy, Clone, Debug)]
+    enum Op {And, Or, Xor, Imply}
+
+    impl Op {
+        /// Evaluates the operator for a single assignment.
+        fn eval(self, xs: &[bool]) -> bool {
+            match (self, xs.len()) {
+                (Op::Xor, 0) => false,
+                (Op::Imply, 1) => xs[0],
+                (Op::And, _) => xs.iter().all(|&x| x),
+                (Op::Or, _) => xs.iter().any(|&x| x),
+                (Op::Xor, _) => xs.iter().filter(|&&x| x).count() == 1,
+                (Op::Imply, _) => xs.windows(2).all(|w| !w[0] || w[1]),
+            }
+        }
+    }
+
+    /// Checks the packed result of an n-argument function against the oracle for every case.
+    fn check_oracle(op: Op, n: usize, f: &PredN) {
+        let mut args = vec![0; n];
+        for chunk in 0..chunks(n) {
+            let r = eval_chunk(n, chunk, &mut args, &mut |vs| f(vs));
+            for bit in 0..64.min(1 << n) {
+                let case = chunk << 6 | bit;
+                let xs: Vec<bool> = (0..n).map(|i| (case >> i) & 1 == 1).collect();
+                assert_eq!((r >> bit) & 1 == 1, op.eval(&xs), "{:?} of {} arguments, case {}", op, n, case);
+            }
+        }
+    }
+
+    #[test]
+    fn operator_oracle() {
+        macro_rules! fixed {
+            ($op:ident, $f:ident($($i:tt),*)) => {
+                (Op::$op, [$($i),*].len(), Box::new(|vs: &[u64]| $f($(vs[$i]),*)) as Box<PredN>)
+            };
+        }
+
+        let fixed = vec![
+            fixed!(And, and(0, 1)),
+            fixed!(And, and3(0, 1, 2)),
+            fixed!(And, and4(0, 1, 2, 3)),
+            fixed!(And, and5(0, 1, 2, 3, 4)),
+            fixed!(And, and6(0, 1, 2, 3, 4, 5)),
+            fixed!(And, and7(0, 1, 2, 3, 4, 5, 6)),
+            fixed!(And, and8(0, 1, 2, 3, 4, 5, 6, 7)),
+            fixed!(And, and9(0, 1, 2, 3, 4, 5, 6, 7, 8)),
+            fixed!(And, and10(0, 1, 2, 3, 4, 5, 6, 7, 8, 9)),
+            fixed!(Or, or(0, 1)),
+            fixed!(Or, or3(0, 1, 2)),
+            fixed!(Or, or4(0, 1, 2, 3)),
+            fixed!(Or, or5(0, 1, 2, 3, 4)),
+            fixed!(Or, or6(0, 1, 2, 3, 4, 5)),
+            fixed!(Or, or7(0, 1, 2, 3, 4, 5, 6)),
+            fixed!(Or, or8(0, 1, 2, 3, 4, 5, 6, 7)),
+            fixed!(Or, or9(0, 1, 2, 3, 4, 5, 6, 7, 8)),
+            fixed!(Or, or10(0, 1, 2, 3, 4, 5, 6, 7, 8, 9)),
+            fixed!(Xor, xor(0, 1)),
+            fixed!(Xor, xor3(0, 1, 2)),
+            fixed!(Xor, xor4(0, 1, 2, 3)),
+            fixed!(Xor, xor5(0, 1, 2, 3, 4)),
+            fixed!(Xor, xor6(0, 1, 2, 3, 4, 5)),
+            fixed!(Xor, xor7(0, 1, 2, 3, 4, 5, 6)),
+            fixed!(Xor, xor8(0, 1, 2, 3, 4, 5, 6, 7)),
+            fixed!(Xor, xor9(0, 1, 2, 3, 4, 5, 6, 7, 8)),
+            fixed!(Xor, xor10(0, 1, 2, 3, 4, 5, 6, 7, 8, 9)),
+            fixed!(Imply, imply(0, 1)),
+            fixed!(Imply, imply3(0, 1, 2)),
+            fixed!(Imply, imply4(0, 1, 2, 3)),
+            fixed!(Imply, imply5(0, 1, 2, 3, 4)),
+            fixed!(Imply, imply6(0, 1, 2, 3, 4, 5)),
+            fixed!(Imply, imply7(0, 1, 2, 3, 4, 5, 6)),
+            fixed!(Imply, imply8(0, 1, 2, 3, 4, 5, 6, 7)),
+            fixed!(Imply, imply9(0, 1, 2, 3, 4, 5, 6, 7, 8)),
+            fixed!(Imply, imply10(0, 1, 2, 3, 4, 5, 6, 7, 8, 9)),
+        ];
+        for (op, n, f) in &fixed {
+            check_oracle(*op, *n, f);
+        }
+
+        let slices: [(Op, &PredN); 4] = [(Op::And, &andn), (Op::Or, &orn), (Op::Xor, &xorn), (Op::Imply, &implyn)];
+        for &(op, f) in &slices {
+            for n in 0..=14 {
+                check_oracle(op, n, f);
+            }
+        }
+    }
+
+    #[test]
+    fn premises() {
+        let candidates: Vec<Box<PredN>> = vec![
+            Box::new(|vs| vs[0]),
+            Box::new(|vs| and(vs[0], vs[1])),
+            Box::new(|_| F),
+            Box::new(|vs| or(vs[0], vs[1])),
+        ];
+        let ranks = rank_premises(3, &candidates, &|vs| and(vs[0], vs[1]));
+        assert_eq!(ranks, vec![(1, 1.0), (0, 0.5), (3, 1.0 / 3.0)]);
+    }
+
+    #[test]
+    fn forall() {
+        assert!(Ab::prove_forall(|x: Ab, p: Pred1| imply(and(x.a, p(x.a)), p(x.b))));
+        assert!(!Ab::prove_forall(|x: Ab, p: Pred1| imply(p(x.a), p(x.b))));
+    }
+
+    #[test]
+    fn all_equal() {
+        assert!(prove3(&mut |a, b, c| eq(alleq(&[a, b, c]), and(eq(a, b), eq(b, c)))));
+        assert!(prove3(&mut |a, b, c| eq(iff!(a, b, c), contr!(a, b, c))));
+        assert!(prove2(&mut |a, b| eq(iff!(a, b), iff(a, b))));
+        assert!(prove4(&mut |a, b, c, d| imply(iff!(a, b, c, d), iff(a, d))));
+        assert_eq!(iff!(P0), T);
+        assert_eq!(alleq(&[]), T);
+        assert_eq!(alleq(&[P0]), T);
+        assert_eq!(count4(&mut |a, b, c, d| alleq(&[a, b, c, d])), 2);
+    }
+
+    #[test]
+    fn majority() {
+        assert_eq!(count3(&mut maj), 4);
+        assert!(prove3(&mut |a, b, c| eq(maj(a, b, c), or3(and(a, b), and(b, c), and(a, c)))));
+        assert!(prove3(&mut |a, b, c| eq(maj!(a, b, c), majn(&[a, b, c]))));
+        assert!(prove3(&mut |c, a, b| eq(ite(c, a, b), or(and(c, a), and(not(c), b)))));
+        assert!(prove2(&mut |a, b| and(eq(ite(T, a, b), a), eq(ite(F, a, b), b))));
+        assert_eq!(majn(&[]), F);
+        assert_eq!(majn(&[P0]), P0);
+        // A tie is not a majority.
+        assert_eq!(count4(&mut |a, b, c, d| maj!(a, b, c, d)), 5);
+        assert_eq!(count5(&mut |a, b, c, d, e| maj!(a, b, c, d, e)), 16);
+        for n in 0..12 {
+            let expected = (0..1_u64 << n).filter(|i| i.count_ones() as usize * 2 > n).count() as u64;
+            assert_eq!(countn(n, &mut |vs| majn(vs)), expected);
+        }
+    }
+
+    #[test]
+    fn enumerate_pred2() {
+        use core::cell::RefCell;
+
+        let tables = RefCell::new(vec![]);
+        assert_eq!(all(&|p: Pred2| {tables.borrow_mut().push(p(P0, P1) & 0b1111); T}), T);
+        assert_eq!(*tables.borrow(), (0..16).collect::<Vec<u64>>());
+        assert_eq!(any(&|p: Pred2| eq(p(P0, P1), imply(P0, P1))), T);
+        assert_eq!(all(&|p: Pred2| eq(p(P0, P1), p(P1, P0))), F);
+        assert_eq!(any(&|p: Pred1| eq(p(P0), F)), T);
+        assert!(Ab::prove_forall(|x: Ab, p: Pred2| imply(and(eq(x.a, x.b), eq(x.b, x.c)), eq(p(x.a, x.b), p(x.c, x.c)))));
+    }
+
+    #[test]
+    fn all_of() {
+        let rules: [&dyn Fn(Ab) -> u64; 3] = [
+            &|x| imply(x.a, x.b),
+            &|x| imply(x.b, x.c),
+            &|x| or(x.a, x.c),
+        ];
+        let x: Ab = Construct::construct(&[P0, P1, P2]);
+        assert_eq!(all_of_fns(&rules, x), and3(imply(P0, P1), imply(P1, P2), or(P0, P2)));
+        assert_eq!(all_of_fns::<Ab>(&[], x), T);
+        assert!(Ab::prove(|x| imply(all_of_fns(&rules, x), x.c)));
+    }
+
+    #[test]
+    fn sample() {
+        use rand::SeedableRng;
+
+        let sols: Vec<Vec<bool>> = solutions(3, |vs| and(or(vs[0], vs[1]), vs[2])).collect();
+        assert_eq!(sols, vec![
+            vec![true, false, true],
+            vec![false, true, true],
+            vec![true, true, true],
+        ]);
+        assert_eq!(solutions(12, andn).count(), 1);
+        assert_eq!(solutions(12, orn).count(), 4095);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        assert_eq!(sample_solutions(3, |vs| and(or(vs[0], vs[1]), vs[2]), 5, &mut rng), sols);
+        let mut hist = [0i32; 3];
+        for _ in 0..3000 {
+            let s = sample_solutions(3, |vs| and(or(vs[0], vs[1]), vs[2]), 1, &mut rng);
+            hist[sols.iter().position(|x| *x == s[0]).unwrap()] += 1;
+        }
+        for &h in &hist {
+            assert!((h - 1000).abs() < 150, "{:?}", hist);
+        }
+    }
+
+    #[test]
+    fn relationships() {
+        assert_eq!(Ab::relationship(|x| x.a, |x| x.b), Relationship::Means);
+        assert_eq!(Ab::relationship(|x| x.a, |x| not(x.b)), Relationship::MeansNot);
+        assert_eq!(Ab::relationship(|x| x.a, |x| x.c), Relationship::Independent);
+        assert_eq!(Ab::relationship(|x| and(x.a, not(x.b)), |x| x.c), Relationship::Contradictory);
+    }
+
+    #[test]
+    fn session() {
+        fn check<S: Copy + Construct + ExtendRules>(fs: &[&dyn Fn(S) -> u64]) {
+            let session = ProveSession::<S>::new();
+            assert_eq!(session.models(), S::count(|_| T) - S::count(|_| F));
+            for &a in fs {
+                assert_eq!(session.count(a), S::count(a));
+                assert_eq!(session.prove(a), S::prove(a));
+                assert_eq!(session.prob(a), S::prob(a));
+                for &b in fs {
+                    assert_eq!(session.relationship(a, b), S::relationship(a, b));
+                    assert_eq!(session.means(a, b), S::means(a, b));
+                    assert_eq!(session.does_not_mean(a, b), S::does_not_mean(a, b));
+                    assert_eq!(session.eq(a, b), S::eq(a, b));
+                    assert_eq!(session.exc(a, b), S::exc(a, b));
+                    assert_eq!(session.imply(a, b), S::imply(a, b));
+                    assert_eq!(session.prob_imply(a, b), S::prob_imply(a, b));
+                }
+            }
+        }
+
+        check::<Ab>(&[&|x| x.a, &|x| x.b, &|x| not(x.c), &|x| and(x.a, not(x.b)), &|x| or(x.a, x.c)]);
+        check::<Abc>(&[&|x| x.ab.a, &|x| x.ab.c, &|x| imply(x.ab.a, x.ab.c), &|_| F]);
+    }
+
+    #[test]
+    fn classical_laws() {
+        for (name, f) in CLASSICAL_LAWS {
+            assert!(proven(3, &mut |vs| f(vs)), "{}", name);
+        }
+        assert!(self_test());
+    }
+
+    #[test]
+    fn margin() {
+        assert_eq!(measure_margin(100, 2, &mut |vs| imply(qual(vs[0], vs[1]), eq(vs[0], vs[1]))), 0);
+        assert_eq!(measure_margin(100, 1, &mut |vs| or(vs[0], qubit(vs[0]))), 1);
+        assert_eq!(measure_margin(1, 2, &mut |vs| and(vs[0], vs[1])), 3);
+    }
+
+    #[test]
+    fn quality() {
+        assert_eq!(quality_score(&|vs| imply(vs[0], vs[1]), &|vs| or(not(vs[0]), vs[1]), 2, 100), 1.0);
+        assert!(quality_score(&|vs| vs[0], &|vs| vs[1], 2, 100) < 0.5);
+    }
+
+    #[test]
+    fn describe() {
+        let rules: Vec<Box<dyn Fn(Ab) -> u64>> = vec![
+            Box::new(|x| imply(x.a, x.b)),
+            Box::new(|x| and(x.a, x.c)),
+        ];
+        assert_eq!(Ab::describe(&["a => b", "a & c"], &rules),
+            "a => b: admits 6, eliminates 2\na & c: admits 2, eliminates 6\nModels: 6 of 8\n");
+    }
+
+    #[test]
+    fn fingerprint() {
+        assert_eq!(Ab::fingerprint(), AbOr::fingerprint());
+        assert_ne!(Ab::fingerprint(), Abc::fingerprint());
+    }
+
+    #[test]
+    fn popcount() {
+        let vs = [P0, P1, and(P0, P1), T, F];
+        let planes = column_popcount(&vs);
+        assert_eq!(planes.len(), 3);
+        for bit in 0..64 {
+            let count: u64 = planes.iter().enumerate().map(|(k, p)| ((p >> bit) & 1) << k).sum();
+            let expected: u64 = vs.iter().map(|v| (v >> bit) & 1).sum();
+            assert_eq!(count, expected);
+        }
+        assert!(column_popcount(&[]).is_empty());
+        assert_eq!(column_popcount(&[T; 8]), vec![0, 0, 0, T]);
+    }
+
+    #[test]
+    fn count_by() {
+        let counts = Ab::count_by(|x| x.c == T);
+        assert_eq!(counts[&true], 3);
+        assert_eq!(counts[&false], 3);
+        assert_eq!(counts.values().sum::<u64>(), Ab::world_count());
+
+        let counts = Abc::count_by(|x| [x.ab.a, x.ab.b, x.ab.c].iter().filter(|&&v| v == T).count());
+        assert_eq!(counts.len(), 4);
+        assert_eq!(counts.values().sum::<u64>(), Abc::world_count());
+        assert_eq!(Abc::world_count(), 4);
+    }
+
+    #[test]
+    fn interval() {
+        let xs = [Interval {lo: 0.0, hi: 2.0}, Interval {lo: 1.0, hi: 3.0}, Interval {lo: -1.0, hi: 1.5}];
+        let mut i = 0;
+        let res = measure(3, || {i += 1; xs[i - 1]});
+        assert_eq!(res, Interval {lo: 1.0, hi: 1.5});
+        assert!(!res.is_empty());
+
+        let mut i = 0;
+        let res = measure(2, || {i += 1; [Interval {lo: 0.0, hi: 1.0}, Interval {lo: 2.0, hi: 3.0}][i - 1]});
+        assert!(res.is_empty());
+        assert!(!measure(0, || Interval {lo: 0.0, hi: 1.0}).is_empty());
+    }
+
+    #[test]
+    fn count11_16() {
+        assert_eq!(count11(&mut |_, _, _, _, _, _, _, _, _, _, _| T), 2048);
+        assert_eq!(count16(&mut |_, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _| T), 65536);
+        assert_eq!(count!(&mut |a, _b, _c, _d, _e, _f, _g, _h, _i, _j, _k, l| and(a, l)), 1024);
+        assert_eq!(count!(&mut |a, b, _c, _d, _e, _f, _g, _h, _i, _j, k, _l, _m, _n, _o, p| {
+            and!(imply(a, b), k, not(p))
+        }), countn(16, &mut |vs| and!(imply(vs[0], vs[1]), vs[10], not(vs[15]))));
+        assert!(prove!(&mut |a, _b, _c, _d, _e, _f, _g, _h, _i, _j, _k, _l, m| {
+            imply(and(a, imply(a, m)), m)
+        }));
+        assert!(!prove!(&mut |a, _b, _c, _d, _e, _f, _g, _h, _i, _j, _k, _l, _m, n| or(a, n)));
+        assert!(prove15(&mut |a, _, _, _, _, _, _, _, _, _, _, _, _, _, o| imply(and(a, o), o)));
+    }
+
+    #[test]
+    fn path1_thresholds() {
+        let splits = [(2, 1, 8), (2, 2, 14), (3, 2, 24), (3, 3, 40), (4, 3, 62), (4, 4, 102), (5, 4, 148), (5, 5, 244)];
+        for &(f, x, len) in &splits {
+            assert_eq!(path1_lennm(f, x), len);
+        }
+        assert!(path1_prove3(&mut |_, _| T));
+        assert!(path1_prove4(&mut |_, _| T));
+        assert!(path1_prove5(&mut |_, _| T));
+        assert!(path1_prove6(&mut |_, _| T));
+        assert!(path1_prove7(&mut |_, _| T));
+        assert!(path1_prove8(&mut |_, _| T));
+        assert!(path1_prove9(&mut |_, _| T));
+        assert!(path1_prove10(&mut |_, _| T));
+        assert!(!path1_prove4(&mut |(a, _), _| a));
+        assert!(!path1_prove10(&mut |_, (a, _, _, _, _)| a));
+    }
+
+    #[test]
+    fn path2() {
+        // Counts the cases of neighbouring levels by brute force.
+        fn related(up: &[u64], down: &[u64]) -> u64 {
+            let constant = or(down.iter().fold(T, |acc, &v| and(acc, v)), down.iter().fold(T, |acc, &v| and(acc, not(v))));
+            let mut at_most_one_zero = T;
+            for i in 0..up.len() {
+                for j in i + 1..up.len() {at_most_one_zero = and(at_most_one_zero, or(up[i], up[j]))}
+            }
+            or(constant, at_most_one_zero)
+        }
+        fn brute<F: FnMut(&[u64], &[u64], &[u64]) -> u64>(f: usize, g: usize, x: usize, fun: &mut F) -> u64 {
+            countn(f + g + x, &mut |vs| {
+                let (fs, gs, xs) = (&vs[..f], &vs[f..f + g], &vs[f + g..]);
+                and3(related(fs, gs), related(gs, xs), fun(fs, gs, xs))
+            })
+        }
+
+        assert_eq!(path2_lennm(3, 2, 1), 48);
+        for f in 0..4 {
+            for g in 0..4 {
+                for x in 0..4 {
+                    assert_eq!(path2_lennm(f, g, x), brute(f, g, x, &mut |_, _, _| T));
+                    let fun = &mut |fs: &[u64], gs: &[u64], xs: &[u64]| {
+                        let v = |vs: &[u64], i: usize| if i < vs.len() {vs[i]} else {F};
+                        imply(eq(v(fs, 0), v(fs, 1)), or(eq(v(gs, 0), v(gs, 1)), v(xs, 0)))
+                    };
+                    assert_eq!(path2_countnm(f, g, x, fun), brute(f, g, x, fun));
+                }
+            }
+        }
+        // Without level 2, the cases are the same as for level 1 and 0.
+        assert_eq!(path2_lennm(0, 3, 2), path1_lennm(3, 2));
+
+        assert!(path2_provenm(3, 2, 1, &mut |_, _, _| T));
+        assert!(path2_prove!(&mut |(f, g, _h), (_a, _b), (_x,)| or(eq(f, g), not(eq(f, g)))));
+        // Equality at level 2 propagates down two levels.
+        assert!(path2_prove!(&mut |(f, g, _h), (a, _b), (x, y)| {
+            imply(and!(not(f), not(g), not(a)), eq(x, y))
+        }));
+        assert!(!path2_prove!(&mut |(f, _g, _h), (a, _b), (x, y)| {
+            imply(and(not(f), not(a)), eq(x, y))
+        }));
+    }
+
+    #[test]
+    fn pathn() {
+        let fun = &mut |ls: &[&[u64]]| {
+            let fs = ls[0];
+            let xs = ls[ls.len() - 1];
+            imply(and(eq(fs[0], fs[1]), not(fs[0])), eq(xs[0], xs[xs.len() - 1]))
+        };
+        for f in 2..5 {
+            for x in 1..5 {
+                assert_eq!(pathn_lennm(&[f, x]), path1_lennm(f, x));
+                assert_eq!(pathn_countnm(&[f, x], fun), path1_countnm(f, x, &mut |fs, xs| fun(&[fs, xs])));
+                assert_eq!(pathn_provenm(&[f, x], fun), path1_provenm(f, x, &mut |fs, xs| fun(&[fs, xs])));
+                for g in 0..4 {
+                    assert_eq!(pathn_lennm(&[f, g, x]), path2_lennm(f, g, x));
+                }
+            }
+        }
+        assert_eq!(pathn_lennm(&[]), 1);
+        assert_eq!(pathn_lennm(&[3]), 8);
+        assert!(pathn_provenm(&[], &mut |_| T));
+        assert!(pathn_provenm(&[2, 2, 2, 2], &mut |_| T));
+        assert!(!pathn_provenm(&[2, 2, 2, 2], &mut |ls| ls[1][0]));
+
+        // Equality at level 3 propagates down three levels.
+        let down = &mut |ls: &[&[u64]]| {
+            imply(and!(not(ls[0][0]), not(ls[0][1]), not(ls[1][0]), not(ls[2][0])), eq(ls[3][0], ls[3][1]))
+        };
+        assert!(pathn_provenm(&[2, 2, 2, 2], down));
+        assert!(!pathn_provenm(&[2, 2, 2, 2], &mut |ls| {
+            imply(and!(not(ls[0][0]), not(ls[1][0]), not(ls[2][0])), eq(ls[3][0], ls[3][1]))
+        }));
+
+        // Compare with brute force, where levels might be empty.
+        let levels = [2, 0, 3, 1, 2];
+        let n: usize = levels.iter().sum();
+        let brute = countn(n, &mut |vs| {
+            let mut rest = vs;
+            let ls: Vec<&[u64]> = levels.iter().map(|&k| {
+                let (a, b) = rest.split_at(k);
+                rest = b;
+                a
+            }).collect();
+            ls.windows(2).fold(T, |acc, w| {
+                let zeros: Vec<u64> = w[0].iter().map(|&v| not(v)).collect();
+                let mut few_zeros = T;
+                for i in 0..zeros.len() {
+                    for j in i + 1..zeros.len() {few_zeros = and(few_zeros, not(and(zeros[i], zeros[j])))}
+                }
+                let constant = or(w[1].iter().fold(T, |a, &v| and(a, v)), w[1].iter().fold(T, |a, &v| and(a, not(v))));
+                and(acc, or(few_zeros, constant))
+            })
+        });
+        assert_eq!(pathn_lennm(&levels), brute);
+        assert_eq!(pathn_countnm(&levels, &mut |_| T), brute);
+    }
+
+    #[test]
+    fn certificate() {
+        let cert = prove_with_certificate(8, &mut |vs| imply(and(vs[0], vs[7]), vs[7])).unwrap();
+        assert!(verify_certificate(&cert));
+        let text = cert.to_string();
+        assert_eq!(text.parse::<Certificate>().unwrap(), cert);
+        assert_eq!(prove_with_certificate(2, &mut |vs| or(vs[0], not(vs[1]))), None);
+
+        let small = prove_with_certificate(2, &mut |vs| or(vs[0], not(vs[0]))).unwrap();
+        assert_eq!(small.to_string(), "2 4 f");
+        assert!(verify_certificate(&small));
+
+        let mut tampered = cert.clone();
+        tampered.table[1] ^= 1 << 5;
+        assert!(!verify_certificate(&tampered));
+        let mut tampered = cert.clone();
+        tampered.table.pop();
+        assert!(!verify_certificate(&tampered));
+        assert!(!verify_certificate(&Certificate {n: 2, count: 3, table: vec![0b111]}));
+        assert!("2 x".parse::<Certificate>().is_err());
+    }
+
+    #[test]
+    fn fold() {
+        let obs = [true, false, true, true];
+        let mut i = 0;
+        let mask = measure_fold(4, 0u64, |acc, x| acc << 1 | x, || {i += 1; obs[i - 1] as u64});
+        assert_eq!(mask, 0b1011);
+        assert_eq!(measure_fold(3, 0, |a: u64, b| a.max(b), || 7), 7);
+        assert!(measure(10, || prove!(&mut |a| imply(a, a))));
+    }
+
+    /// A system assuming the path semantical core axiom.
+    #[derive(Copy, Clone)]
+    struct Psq {
+        a: u64,
+        b: u64,
+        c: u64,
+        d: u64,
+    }
+
+    impl Construct for Psq {
+        fn construct(vs: &[u64]) -> Self {Psq {a: vs[0], b: vs[1], c: vs[2], d: vs[3]}}
+    }
+
+    impl CoreRules for Psq {
+        fn core_rules(&self) -> u64 {
+            and3(ps_core(self.a, self.b, self.c, self.d), imply(self.a, self.c), imply(self.b, self.d))
+        }
+    }
+
+    impl BaseSystem for Psq {}
+
+    /// Same as `Psq`, but denying the quality the core axiom concludes.
+    #[derive(Copy, Clone)]
+    struct PsqBad {
+        psq: Psq,
+    }
+
+    impl Construct for PsqBad {
+        fn construct(vs: &[u64]) -> Self {PsqBad {psq: Construct::construct(vs)}}
+    }
+
+    impl CoreRules for PsqBad {
+        fn core_rules(&self) -> u64 {
+            let Psq {a, b, c, d} = self.psq;
+            and!(qual(a, b), not(qual(c, d)))
+        }
+    }
+
+    impl ExtendRules for PsqBad {
+        type Inner = Psq;
+        fn inner(&self) -> &Psq {&self.psq}
+        fn extend_rules(&self, _: &Psq) -> u64 {T}
+    }
+
+    #[test]
+    fn ps_consistency() {
+        assert!(Psq::consistent());
+        assert!(Psq::check_ps_consistency(100));
+        assert!(!PsqBad::consistent());
+        assert!(!PsqBad::check_ps_consistency(100));
+    }
+
+    #[test]
+    fn vec_proof() {
+        // The proof in `examples/vars.rs` using only the propositions it reads.
+        let (a, f, i, p, q, r, ab, ae) = (0, 1, 2, 3, 4, 5, 6, 7);
+        assert!(proven_vec(8,
+            |vs| xorn(&[vs[a], vs[f], vs[i], vs[p], vs[ab], vs[ae], vs[p], vs[q], vs[r], vs[ae]]),
+            |vs| imply(vs[a], not(vs[ae]))
+        ));
+        assert!(!proven_vec(8, |vs| vs[a], |vs| vs[ae]));
+    }
+
+    #[test]
+    fn eliminations() {
+        let rules: Vec<Box<dyn Fn(Ab) -> u64>> = vec![
+            Box::new(|x| and3(x.a, x.b, x.c)),
+            Box::new(|x| or3(x.a, x.b, x.c)),
+            Box::new(|x| imply(x.a, x.b)),
+            Box::new(|_| T),
+        ];
+        assert_eq!(Ab::rule_eliminations(&rules), vec![7, 1, 2, 0]);
+    }
+
+    #[test]
+    fn temporal() {
+        // A bit that toggles at every step over a 3-step trace is eventually true.
+        assert!(prove3(&mut |a, b, c| {
+            imply(and(eq(b, not(a)), eq(c, not(b))), eventually(&[a, b, c]))
+        }));
+        assert!(!prove3(&mut |a, b, c| {
+            imply(and(eq(b, not(a)), eq(c, not(b))), eventually(&[a, c]))
+        }));
+        assert!(prove2(&mut |a, b| eq(globally(&[a, b]), not(eventually(&[not(a), not(b)])))));
+        // `a` holds until `b` holds.
+        assert!(prove3(&mut |a, b, c| eq(until(&[a, b, c], &[F, F, T]), and(a, b))));
+        assert!(prove3(&mut |a, b, c| eq(until(&[a, b, F], &[F, c, F]), and(a, c))));
+        assert!(prove3(&mut |a, b, c| imply(until(&[a, b, c], &[a, b, c]), eventually(&[a, b, c]))));
+    }
+
+    #[test]
+    fn report() {
+        assert_eq!(report_line("Modus ponens", true), "Modus ponens: true");
+        assert!(report!("Modus ponens", &mut |a, b| imply(and(a, imply(a, b)), b)));
+        assert!(!report!("Affirming the consequent", &mut |a, b| imply(and(b, imply(a, b)), a)));
+        assert!(report!("Path", &mut |(f, g), (x, y)| imply(and(eq(f, g), eq(x, y)), eq(and(f, x), and(g, y)))));
+        assert!(report_measure!("Quality", 10, &mut |a, b| imply(q(a, b), eq(a, b))));
+        assert!(!report_measure!("Self quality", 10, &mut |a| q(a, a)));
+    }
+
+    #[test]
+    fn counterexample() {
+        assert_eq!(measure_counterexample(10, 2, &mut |vs| imply(q(vs[0], vs[1]), eq(vs[0], vs[1]))), None);
+
+        let mut fun = |vs: &[u64]| imply(qubit(vs[0]), vs[0]);
+        let (seed, xs) = measure_counterexample(100, 1, &mut fun).unwrap();
+        assert_eq!(xs, vec![false]);
+        assert!(!with_round_seed(seed, || proven(1, &mut fun)));
+        assert_eq!(with_round_seed(seed, || solutions(1, |vs| not(fun(vs))).next()), Some(xs));
+    }
+
+    #[test]
+    fn prop_operators() {
+        assert!(prove!(&mut |a, b, c| eq(*((Prop(a) & Prop(b)) >> Prop(c)), imply(and(a, b), c))));
+        assert!(prove!(&mut |a, b, c| eq(*((Prop(a) | !Prop(b)) ^ Prop(c)), xor(or(a, not(b)), c))));
+        assert_eq!(u64::from(Prop(T) >> Prop(F)), F);
+    }
+
+    #[test]
+    fn negated_gates() {
+        assert_eq!(count2(&mut nand), 3);
+        assert_eq!(count2(&mut nor), 1);
+        assert_eq!(count2(&mut xnor), 2);
+        assert!(prove!(&mut |a, b, c| eq(nand!(a, b, c), not(and3(a, b, c)))));
+        assert!(prove!(&mut |a, b, c| eq(nor!(a, b, c), not(or3(a, b, c)))));
+        assert!(prove!(&mut |a, b, c| eq(xnor!(a, b, c), not(xor3(a, b, c)))));
+        assert!(prove!(&mut |a, b, c, d| eq(nandn(&[a, b, c, d]), nand4(a, b, c, d))));
+        assert!(prove!(&mut |a, b, c, d| eq(norn(&[a, b, c, d]), nor4(a, b, c, d))));
+        assert!(prove!(&mut |a, b, c, d| eq(xnorn(&[a, b, c, d]), xnor4(a, b, c, d))));
+        assert!(prove!(&mut |a, b| eq(nand!(a, b, a, b, a, b, a, b, a, b, a, b), nand(a, b))));
+        assert!(prove!(&mut |a, b| eq(nor!(a, b, a, b, a, b, a, b, a, b, a, b), nor(a, b))));
+        assert!(prove!(&mut |a| eq(nand!(a), not(a))));
+    }
+
+    #[test]
+    fn consistent_subsets() {
+        let rules: Vec<Box<PredN>> = vec![
+            Box::new(|vs| vs[0]),
+            Box::new(|vs| imply(vs[0], vs[1])),
+            Box::new(|vs| not(vs[1])),
+        ];
+        assert_eq!(maximal_consistent_subsets(2, &rules), vec![vec![0, 1], vec![0, 2], vec![1, 2]]);
+
+        let rules: Vec<Box<PredN>> = vec![
+            Box::new(|vs| vs[0]),
+            Box::new(|vs| not(vs[0])),
+            Box::new(|vs| vs[7]),
+        ];
+        assert_eq!(maximal_consistent_subsets(8, &rules), vec![vec![0, 2], vec![1, 2]]);
+    }
+
+    #[test]
+    fn counterexample_proofs() {
+        assert_eq!(prove_ce2(&mut |a, b| imply(and(a, imply(a, b)), b)), Ok(()));
+        assert_eq!(prove_ce2(&mut |a, b| imply(b, a)), Err(vec![false, true]));
+        assert_eq!(prove_ce!(&mut |a, b, c| imply(and(a, b), c)), Err(vec![true, true, false]));
+        assert_eq!(prove_ce!(&mut |a, b, c, d, e, f, g, h| imply(and(a, h), or6(b, c, d, e, f, g))),
+            Err(vec![true, false, false, false, false, false, false, true]));
+        assert_eq!(prove_ce!(&mut |a, b, c, d, e, f, g| or(and6(a, b, c, d, e, f), imply(g, g))), Ok(()));
+    }
+
+    #[test]
+    fn abduction() {
+        // The grass is wet if it rained or the sprinkler was on.
+        let rules = |vs: &[u64]| imply(or(vs[0], vs[1]), vs[2]);
+        let pool: Vec<Box<PredN>> = vec![
+            Box::new(|vs| vs[0]),
+            Box::new(|vs| vs[1]),
+            Box::new(|vs| not(vs[2])),
+            Box::new(|vs| and(vs[0], vs[1])),
+        ];
+        let wet = |vs: &[u64]| vs[2];
+        assert_eq!(abduce(3, &rules, &pool, &wet), vec![vec![0], vec![1], vec![3]]);
+        assert_eq!(abduce(3, &rules, &pool[2..], &wet), vec![vec![1]]);
+        assert_eq!(abduce(3, &rules, &pool[2..3], &wet), Vec::<Vec<usize>>::new());
+        assert_eq!(abduce(3, &|_| T, &pool, &|vs| or(vs[2], not(vs[2]))), vec![vec![]]);
+    }
+
+    #[test]
+    fn find_models() {
+        assert_eq!(find_model(2, &mut |v| and(v[0], v[1])), Some(vec![true, true]));
+        assert_eq!(find_model(2, &mut |v| and(v[0], not(v[0]))), None);
+        assert_eq!(find_model(8, &mut |v| and(v[7], v[1])),
+            Some(vec![false, true, false, false, false, false, false, true]));
+        assert_eq!(find_all_models(2, &mut |v| imply(v[0], v[1])),
+            vec![vec![false, false], vec![false, true], vec![true, true]]);
+        assert_eq!(find_all_models(10, &mut |v| orn(v)).len(), 1023);
+    }
+
+    #[test]
+    fn weighted() {
+        for n in 0..9 {
+            let f = &mut |vs: &[u64]| or(xorn(vs), andn(vs));
+            let expected = countn(n, f) as f64 / 2f64.powi(n as i32);
+            assert!((weighted_count(n, &vec![0.5; n], f) - expected).abs() < 1e-12);
+        }
+        assert!((weighted_count(2, &[0.3, 0.6], &mut |vs| and(vs[0], vs[1])) - 0.18).abs() < 1e-12);
+        let ws = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
+        let none: f64 = ws.iter().map(|w| 1.0 - w).product();
+        assert!((weighted_count(8, &ws, &mut |vs| orn(vs)) - (1.0 - none)).abs() < 1e-12);
+        assert!((weighted_count(8, &ws, &mut |vs| vs[7]) - 0.8).abs() < 1e-12);
+        assert_eq!(weighted_count(3, &[1.0, 0.0, 1.0], &mut |vs| and(vs[0], not(vs[1]))), 1.0);
+    }
+
+    #[test]
+    fn models_lazy_fn() {
+        // The test `models` shadows the function.
+        assert_eq!(models(3, &mut |v| and(v[0], v[1])).count(), 2);
+        assert_eq!(models(3, &mut |v| and(v[0], v[1])).count() as u64, count3(&mut |a, b, _| and(a, b)));
+        assert_eq!(models(2, &mut |v| imply(v[0], v[1])).collect::<Vec<_>>(),
+            find_all_models(2, &mut |v| imply(v[0], v[1])));
+
+        // Only the first cases are evaluated when taking a few solutions.
+        let mut calls = 0;
+        let mut f = |v: &[u64]| {calls += 1; not(and(v[0], v[39]))};
+        let first: Vec<Vec<bool>> = models(40, &mut f).take(3).collect();
+        assert_eq!(first.len(), 3);
+        assert!(first.iter().all(|m| m.len() == 40 && !(m[0] && m[39])));
+        assert_eq!(calls, 1);
+    }
+
+    /// A system with too many arguments to enumerate all worlds.
+    #[derive(Copy, Clone)]
+    struct Wide([u64; 40]);
+
+    impl Construct for Wide {
+        fn construct(vs: &[u64]) -> Self {
+            let mut xs = [0; 40];
+            xs.copy_from_slice(&vs[..40]);
+            Wide(xs)
+        }
+    }
+
+    impl CoreRules for Wide {
+        fn core_rules(&self) -> u64 {imply(self.0[0], self.0[39])}
+    }
+
+    impl BaseSystem for Wide {}
+
+    #[test]
+    fn models_lazy() {
+        assert_eq!(Wide::models_iter(|w| not(w.0[1])).take(3).count(), 3);
+        for w in Wide::models_iter(|w| and(w.0[1], w.0[2])).take(3) {
+            assert_eq!((w.0[1], w.0[2]), (T, T));
+            assert_eq!(imply(w.0[0], w.0[39]), T);
+        }
+        assert_eq!(<(Ab, ())>::models_iter(|(ab, ())| ab.a).count(), 2);
+        assert_eq!(Ab::models_iter(|_| T).count() as u64, Ab::world_count());
+    }
+
+    #[test]
+    fn diff_table() {
+        let diff = table_diff(2, andn, orn);
+        assert_eq!(diff.rows.len(), 4);
+        assert_eq!(diff.differing().map(|(xs, _, _)| xs.clone()).collect::<Vec<_>>(),
+            vec![vec![true, false], vec![false, true]]);
+        assert_eq!(diff.to_string(), "0 0 | 0 0\n1 0 | 0 1 *\n0 1 | 0 1 *\n1 1 | 1 1\n");
+        let diff = table_diff(8, |vs| vs[7], |vs| and(vs[7], vs[0]));
+        assert_eq!(diff.rows.len(), 256);
+        assert_eq!(diff.differing().count(), 64);
+    }
+
+    #[test]
+    fn cube() {
+        let mut f = |vs: &[u64]| or(and(vs[0], vs[3]), xor(vs[1], vs[11]));
+        assert_eq!(count_cube(12, &[true], &mut f) + count_cube(12, &[false], &mut f), countn(12, &mut f));
+        let prefixes = [[false, false], [false, true], [true, false], [true, true]];
+        assert_eq!(prefixes.iter().map(|p| count_cube(12, p, &mut f)).sum::<u64>(), countn(12, &mut f));
+        assert_eq!(count_cube(2, &[true, false], &mut |vs| and(vs[0], not(vs[1]))), 1);
+        assert_eq!(count_cube(2, &[], &mut |vs| and(vs[0], vs[1])), 1);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel() {
+        let f = |vs: &[u64]| or(and(vs[0], vs[1]), xor(vs[2], vs[vs.len() - 1]));
+        for n in [3, 12, 20] {
+            assert_eq!(countn_par(n, &f), countn(n, &mut |vs| f(vs)));
+        }
+        assert!(proven_par(20, &|vs| imply(vs[19], or(vs[19], vs[2]))));
+    }
+
+    #[test]
+    fn classical() {
+        fn h(vs: &[u64]) -> u64 {
+            or3(and(vs[0], vs[9]), xor(vs[1], vs[8]), imply(and(vs[2], vs[7]), or3(vs[3], vs[4], and(vs[5], vs[6]))))
+        }
+        assert_eq!(count7_classical(&mut |a, b, c, d, e, f, g| h(&[a, b, c, d, e, f, g, T, F, T])),
+            count7(&mut |a, b, c, d, e, f, g| h(&[a, b, c, d, e, f, g, T, F, T])));
+        assert_eq!(count8_classical(&mut |a, b, c, d, e, f, g, i| h(&[a, b, c, d, e, f, g, i, F, T])),
+            count8(&mut |a, b, c, d, e, f, g, i| h(&[a, b, c, d, e, f, g, i, F, T])));
+        assert_eq!(count9_classical(&mut |a, b, c, d, e, f, g, i, j| h(&[a, b, c, d, e, f, g, i, j, T])),
+            count9(&mut |a, b, c, d, e, f, g, i, j| h(&[a, b, c, d, e, f, g, i, j, T])));
+        assert_eq!(count10_classical(&mut |a, b, c, d, e, f, g, i, j, k| h(&[a, b, c, d, e, f, g, i, j, k])),
+            count10(&mut |a, b, c, d, e, f, g, i, j, k| h(&[a, b, c, d, e, f, g, i, j, k])));
+    }
+
+    #[test]
+    fn qubit_whitening() {
+        call(|| {
+            let ones = |on: bool| with_qubit_whitening(on, || {
+                (0..2000).map(|a| qubit(a << 1).count_ones() as u64).sum::<u64>()
+            });
+            let total = 2000.0 * 64.0;
+            let plain = ones(false) as f64 / total;
+            let whitened = ones(true) as f64 / total;
+            assert!((plain - 0.5).abs() < 0.01, "{}", plain);
+            assert!((whitened - 0.5).abs() < 0.01, "{}", whitened);
+
+            assert_eq!(qubit(P0), with_qubit_whitening(false, || qubit(P0)));
+            assert_ne!(qubit(P0), with_qubit_whitening(true, || qubit(P0)));
+            assert_eq!(with_qubit_whitening(true, || qubit(P0)), with_qubit_whitening(true, || qubit(P0)));
+            T
+        });
+    }
+
+    #[test]
+    fn qubit_ctx() {
+        let (a, b) = (QubitCtx::new(1), QubitCtx::new(2));
+        assert_eq!(with_round_seed(1, || call(|| {
+            assert_eq!(QubitCtx::current(), Some(a));
+            assert_eq!(with_qubit_namespace(4, QubitCtx::current), Some(a.with_namespace(4)));
+            assert_eq!(a.qubit(P0), qubit(P0));
+            assert_eq!(a.with_namespace(3).qubit(P1), with_qubit_namespace(3, || qubit(P1)));
+            assert_eq!(a.with_whitening(true).qubit(P1), with_qubit_whitening(true, || qubit(P1)));
+            T
+        })), T);
+        assert_eq!(QubitCtx::current(), None);
+        assert_ne!(a.qubit(P0), b.qubit(P0));
+        assert_eq!(a.qubit(not(P0)), not(a.qubit(P0)));
+
+        // Threads do not share a round, so the contexts are passed explicitly.
+        let run = |ctx: QubitCtx| std::thread::spawn(move || {
+            (0..1000).map(|i| ctx.qubit(i)).collect::<Vec<u64>>()
+        });
+        let (ta, tb) = (run(a), run(b));
+        let (xa, xb) = (ta.join().unwrap(), tb.join().unwrap());
+        assert_eq!(xa, (0..1000).map(|i| a.qubit(i)).collect::<Vec<u64>>());
+        assert_eq!(xb, (0..1000).map(|i| b.qubit(i)).collect::<Vec<u64>>());
+        assert!(xa.iter().zip(&xb).all(|(x, y)| x != y));
+
+        assert!(measure_ctx(10, |ctx| prove!(&mut |a| eq(ctx.qubit(a), qubit(a)))));
+        assert!(with_round_seed(5, || measure_ctx(10, |ctx| ctx.round == 5)));
+        assert_eq!(measure_seeded(5, 3, || measure_ctx(1, |ctx| ctx.round)), 5);
+        assert!(with_qubit_namespace(3, || measure_ctx(10, |ctx| ctx.namespace == 3)));
+        assert!(measure_ctx(10, |ctx| {
+            let ctx = *ctx;
+            std::thread::spawn(move || ctx.qubit(P0)).join().unwrap() == ctx.qubit(P0)
+        }));
+    }
+
+    #[test]
+    fn prove_sys() {
+        assert!(prove_sys!(Ab {a, b}, imply(a, b)));
+        assert!(!prove_sys!(Ab {a, b}, imply(b, a)));
+        assert!(prove_sys!(Ab {}, T));
+        assert!(prove_sys!(Abc {ab}, imply(ab.a, ab.c)));
+        let x = F;
+        assert!(prove_sys!(Ab {a, b,}, or(x, imply(and(a, not(b)), F))));
+    }
 }